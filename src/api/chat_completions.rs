@@ -1,69 +1,226 @@
 use serde_json::json;
+use std::time::Instant;
 
-use super::Instance;
-use crate::json::chat_completions::ChatCompletionsResponse;
+use super::{GenerateOptions, Instance};
+use crate::board::Board;
+use crate::cache::Cache;
+use crate::json::chat_completions::{ChatCompletionsResponse, Timing};
+use crate::language::Language;
 
-const SYSTEM_PROMPT: &str = "
-You are the spymaster in Codenames.
-I will give you a list of [agent word], followed by a list of [avoid word].
-Try to link [agent word] together.
-Tro to avoid [avoid word].
+const PICTURES_SYSTEM_PROMPT: &str = "
+You are the spymaster in Codenames Pictures.
+The cards are pictures, labeled with their coordinate on the board: a column letter from A to E and a row number from 1 to 4.
+I will give you a list of [agent card], followed by a list of [avoid card], and then every picture after its coordinate.
+Try to link [agent card] together with a single word.
+Try to avoid [avoid card].
 Answer in this format:
-[clue word], [number of agent words], [agent word], [agent word], [agent word]
+[clue word], [number of agent cards], [agent card], [agent card], [agent card]
 ...
 Here are the requirements:
-- Always answer in lower case.
+- Always answer in lower case, except for the coordinates.
+- Refer to cards by coordinate only, like B3.
 - No special characters.
 - No intro or outro.
 - No explanations.
-- Give 5-10 [clue word].
-- Each [clue word] should link at least 2 [agent word].
+- Give {min_clues}-{max_clues} [clue word].
+- Each [clue word] should link at least {min_linked} [agent card].
 ";
 
+/// Rough number of prompt tokens used by one image
+const IMAGE_TOKENS: usize = 765;
+
 impl Instance {
     pub async fn post_chat_completions(
         &self,
-        link_words: &[String],
-        avoid_words: &[String],
+        board: &Board,
         model_id: &String,
+        options: &GenerateOptions,
+    ) -> Result<ChatCompletionsResponse, Box<dyn std::error::Error>> {
+        let mut request_body = Self::build_request_body(board, model_id, options);
+        options.generation.get(model_id).apply(&mut request_body);
+
+        self.send_chat_completions(&request_body)
+            .await
+            .map_err(|e| format!("Failed to fetch clue collection from API server: {e}").into())
+    }
+
+    /// Send any chat completions request, or reuse the cached response to the exact same
+    /// request, and time it
+    pub(super) async fn send_chat_completions(
+        &self,
+        request_body: &serde_json::Value,
     ) -> Result<ChatCompletionsResponse, Box<dyn std::error::Error>> {
-        let request_body = Self::build_request_body(link_words, avoid_words, model_id);
+        // Reuse an earlier response to the exact same request
+        let start = Instant::now();
+        let cache_key = Cache::key(&self.base_url, request_body);
+        if let Some(cached) = self.cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+            if let Ok(mut parsed_response) =
+                serde_json::from_str::<ChatCompletionsResponse>(&cached)
+            {
+                parsed_response.timing = Timing {
+                    latency: start.elapsed(),
+                    cached: true,
+                };
+                return Ok(parsed_response);
+            }
+        }
 
         // Get response from API endpoint
         let response = self
             .client
             .post(format!("{}chat/completions", self.base_url))
-            .bearer_auth(&self.api_key)
-            .json(&request_body)
+            .bearer_auth(self.api_key.expose())
+            .json(request_body)
             .send()
-            .await
-            .map_err(|e| format!("Failed to fetch clue collection from API server: {e}"))?;
+            .await?;
+        let raw_response = response.text().await?;
+        let latency = start.elapsed();
 
-        let parsed_response = response
-            .json::<ChatCompletionsResponse>()
-            .await
-            .map_err(|e| format!("Failed to parse clues from API server: {e}"))?;
+        let mut parsed_response = serde_json::from_str::<ChatCompletionsResponse>(&raw_response)
+            .map_err(|e| format!("Invalid response: {e}"))?;
+        parsed_response.timing = Timing {
+            latency,
+            cached: false,
+        };
+
+        // Only successful responses make it here, so they are safe to cache
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(&cache_key, &raw_response) {
+                eprintln!("Failed to write to cache at {}: {e}", cache.dir().display());
+            }
+        }
 
         Ok(parsed_response)
     }
 
+    /// Roughly estimate the number of prompt tokens of a request, at about 4 characters per token
+    pub fn estimate_prompt_tokens(
+        board: &Board,
+        model_id: &String,
+        options: &GenerateOptions,
+    ) -> usize {
+        let request_body = Self::build_request_body(board, model_id, options);
+
+        // Add a few tokens for the role and formatting of each message
+        request_body["messages"]
+            .as_array()
+            .map(|messages| {
+                messages
+                    .iter()
+                    .map(|message| estimate_content_tokens(&message["content"]) + 4)
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
     fn build_request_body(
-        link_words: &[String],
-        avoid_words: &[String],
+        board: &Board,
         model_id: &String,
+        options: &GenerateOptions,
     ) -> serde_json::Value {
-        // Aggregate two sets of words into one prompt
-        let content = format!(
-            "To Link:\n{}\n\nTo Avoid:\n{}",
-            link_words.join("\n"),
-            avoid_words.join("\n")
+        if !board.pictures().is_empty() {
+            return Self::build_pictures_request_body(board, model_id, options);
+        }
+
+        let template = options.language.template();
+        let content = Self::build_board_text(board, options);
+
+        json!({
+            "messages": [
+                {
+                    "role": "system",
+                    "content": options.rules.for_board(board).fill(template.system_prompt),
+                },
+                {
+                    "role": "user",
+                    "content": content
+                }
+            ],
+            "model": model_id
+        })
+    }
+
+    /// The words or coordinates of `board` and the state of the game, with the headers of the
+    /// language of the board. Shared by the word and the Pictures prompts.
+    fn build_board_text(board: &Board, options: &GenerateOptions) -> String {
+        // Aggregate two sets of words into one prompt, in the language of the board
+        let template = options.language.template();
+        let mut content = format!(
+            "{}\n{}\n\n{}\n{}",
+            template.to_link,
+            board.to_link().join("\n"),
+            template.to_avoid,
+            board.to_avoid().join("\n")
         );
 
+        // Assassins end the game, so no clue may point to them at all
+        if !board.assassins().is_empty() {
+            content.push_str(&format!(
+                "\n\n{}\n{}",
+                template.assassins,
+                board.assassins().join("\n")
+            ));
+        }
+
+        // Clues can't be reused within a game
+        if !options.used_clues.is_empty() {
+            content.push_str(&format!(
+                "\n\n{}\n{}",
+                template.used_clues,
+                options.used_clues.join("\n")
+            ));
+        }
+
+        // The spymaster wants these words linked this turn
+        if !options.targets.is_empty() {
+            content.push_str(&format!(
+                "\n\n{}\n{}",
+                template.targets,
+                options.targets.join("\n")
+            ));
+        }
+
+        // Near the end of the game a safe clue for one word beats a risky one
+        if options.rules.is_end_game(board) {
+            content.push_str(&format!("\n\n{}", template.end_game));
+        }
+
+        content
+    }
+
+    /// Send every picture as an image content part, preceded by its coordinate,
+    /// so that vision models can tell the cards apart
+    fn build_pictures_request_body(
+        board: &Board,
+        model_id: &String,
+        options: &GenerateOptions,
+    ) -> serde_json::Value {
+        let text = Self::build_board_text(board, options);
+        let mut content = vec![json!({ "type": "text", "text": text })];
+
+        for picture in board.pictures() {
+            content.push(json!({ "type": "text", "text": picture.coordinate }));
+            content.push(json!({
+                "type": "image_url",
+                "image_url": { "url": picture.url }
+            }));
+        }
+
+        // Pictures have no words to read, so the language only applies to the clues and
+        // the headers
+        let mut system_prompt = options.rules.for_board(board).fill(PICTURES_SYSTEM_PROMPT);
+        if options.language != Language::English {
+            system_prompt.push_str(&format!(
+                "- Give every [clue word] in {}.\n",
+                options.language.name()
+            ));
+        }
+
         json!({
             "messages": [
                 {
                     "role": "system",
-                    "content": SYSTEM_PROMPT,
+                    "content": system_prompt,
                 },
                 {
                     "role": "user",
@@ -75,9 +232,28 @@ impl Instance {
     }
 }
 
+/// About 4 characters per token for text, and a fixed amount per image
+fn estimate_content_tokens(content: &serde_json::Value) -> usize {
+    match content {
+        serde_json::Value::String(text) => text.chars().count().div_ceil(4),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .map(|part| match part["type"].as_str() {
+                Some("image_url") => IMAGE_TOKENS,
+                _ => estimate_content_tokens(&part["text"]),
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::pictures::Picture;
+    use crate::cache::{CacheMode, DEFAULT_TTL};
+    use crate::clues::rules::ClueRules;
+    use crate::generation::{GenerationParams, GenerationTable};
     use httpmock::prelude::*;
 
     #[tokio::test]
@@ -99,21 +275,166 @@ mod tests {
 
         // Get responses from mock server
         api_instance
-            .post_chat_completions(&Vec::<String>::new(), &Vec::<String>::new(), &String::new())
+            .post_chat_completions(
+                &Board::new(vec![], vec![]),
+                &String::new(),
+                &GenerateOptions::default(),
+            )
             .await
             .unwrap();
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_post_chat_completions_generation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        std::fs::write(&config_file, "[generation.\"model-a\"]\nseed = 7\n").unwrap();
+        let config = crate::configs::config::Config::load(&config_file).unwrap();
+
+        // Settings from the config and the command line end up in the request
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .json_body_partial(r#"{ "model": "model-a", "seed": 7, "temperature": 0.5 }"#);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions.json");
+        });
+
+        let api_instance = Instance::builder(server.url("/")).build();
+        let overrides = GenerationParams {
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+        let options = GenerateOptions {
+            generation: GenerationTable::new(&config, overrides).unwrap(),
+            ..Default::default()
+        };
+        api_instance
+            .post_chat_completions(
+                &Board::new(vec![], vec![]),
+                &"model-a".to_string(),
+                &options,
+            )
+            .await
+            .unwrap();
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_post_chat_completions_cached() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions.json");
+        });
+
+        // Create an API instance with a cache in a temporary directory
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut api_instance = Instance::new().unwrap_or_default();
+        api_instance.set_base_url(server.url("/"));
+        api_instance.set_cache(Cache::with_dir(
+            temp_dir.path(),
+            CacheMode::Enabled,
+            DEFAULT_TTL,
+        ));
+
+        // The second identical request should be served from the cache
+        let board = Board::new(vec!["link".to_string()], vec![]);
+        let mut cached = vec![];
+        for _ in 0..2 {
+            let response = api_instance
+                .post_chat_completions(&board, &"model".to_string(), &GenerateOptions::default())
+                .await
+                .unwrap();
+            cached.push(response.timing.cached);
+        }
+        mock.assert_hits(1);
+        assert_eq!(cached, vec![false, true]);
+
+        // A different model is a different request
+        api_instance
+            .post_chat_completions(
+                &board,
+                &"other-model".to_string(),
+                &GenerateOptions::default(),
+            )
+            .await
+            .unwrap();
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_post_chat_completions_cached_per_server() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let board = Board::new(vec!["link".to_string()], vec![]);
+
+        // Two providers with the same model ID share the cache directory, not the answers
+        let servers = [
+            MockServer::start_async().await,
+            MockServer::start_async().await,
+        ];
+        for server in &servers {
+            let mock = server.mock(|when, then| {
+                when.method(POST).path("/chat/completions");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body_from_file("resources/tests/mock_responses/chat_completions.json");
+            });
+
+            let mut api_instance = Instance::builder(server.url("/")).build();
+            api_instance.set_cache(Cache::with_dir(
+                temp_dir.path(),
+                CacheMode::Enabled,
+                DEFAULT_TTL,
+            ));
+            let response = api_instance
+                .post_chat_completions(&board, &"model".to_string(), &GenerateOptions::default())
+                .await
+                .unwrap();
+            assert!(!response.timing.cached);
+            mock.assert_hits(1);
+        }
+    }
+
+    #[test]
+    fn test_estimate_prompt_tokens() {
+        let link_words = vec![
+            "link1".to_string(),
+            "link2".to_string(),
+            "link3".to_string(),
+        ];
+        let avoid_words = vec!["avoid1".to_string()];
+        let model_id = "model".to_string();
+
+        let board = Board::new(link_words, avoid_words);
+        let estimate =
+            Instance::estimate_prompt_tokens(&board, &model_id, &GenerateOptions::default());
+        let content = "To Link:\nlink1\nlink2\nlink3\n\nTo Avoid:\navoid1";
+        let system_prompt = ClueRules::default().fill(Language::English.template().system_prompt);
+        let expected =
+            system_prompt.chars().count().div_ceil(4) + content.chars().count().div_ceil(4) + 8;
+        assert_eq!(estimate, expected);
+    }
+
     #[test]
     fn test_build_request_body() {
         // Mock input data
-        let link_words = vec!["link1".to_string(), "link2".to_string()];
+        let link_words = vec![
+            "link1".to_string(),
+            "link2".to_string(),
+            "link3".to_string(),
+        ];
         let avoid_words = vec!["avoid1".to_string(), "avoid2".to_string()];
         let model_id = "model".to_string();
 
         // Assign result to the result of build_request_body() method
-        let result = Instance::build_request_body(&link_words, &avoid_words, &model_id);
+        let board = Board::new(link_words.clone(), avoid_words.clone());
+        let result = Instance::build_request_body(&board, &model_id, &GenerateOptions::default());
 
         // Format expected content
         let expected_content = format!(
@@ -128,7 +449,7 @@ mod tests {
             "messages": [
                 {
                     "role": "system",
-                    "content": SYSTEM_PROMPT,
+                    "content": ClueRules::default().fill(Language::English.template().system_prompt),
                 },
                 {
                     "role": "user",
@@ -139,4 +460,148 @@ mod tests {
         });
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_build_request_body_keeps_spelling() {
+        let mut board = Board::new(
+            vec![
+                "Straße".to_string(),
+                "STRASSE".to_string(),
+                "Brücke".to_string(),
+            ],
+            vec!["Fluss".to_string()],
+        );
+        board.normalize(Language::German).unwrap();
+        let options = GenerateOptions::default().language(Language::German);
+        let result = Instance::build_request_body(&board, &"model".to_string(), &options);
+
+        let content = result["messages"][1]["content"].as_str().unwrap();
+        assert!(content.contains("Straße\nBrücke"));
+        assert!(!content.contains("strasse"));
+    }
+
+    #[test]
+    fn test_build_request_body_pictures() {
+        let pictures = vec![
+            Picture {
+                coordinate: "A1".to_string(),
+                url: "data:image/png;base64,AAAA".to_string(),
+            },
+            Picture {
+                coordinate: "B1".to_string(),
+                url: "data:image/png;base64,BBBB".to_string(),
+            },
+        ];
+        let board = Board::new(vec!["A1".to_string()], vec!["B1".to_string()])
+            .with_assassins(vec!["B1".to_string()])
+            .with_pictures(pictures);
+
+        let result =
+            Instance::build_request_body(&board, &"model".to_string(), &GenerateOptions::default());
+        assert_eq!(
+            result["messages"][0]["content"],
+            ClueRules::default()
+                .for_board(&board)
+                .fill(PICTURES_SYSTEM_PROMPT)
+        );
+
+        let content = result["messages"][1]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 5);
+        assert!(content[0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("To Link:\nA1\n\nTo Avoid:\nB1"));
+        assert_eq!(content[3], json!({ "type": "text", "text": "B1" }));
+        assert_eq!(content[4]["image_url"]["url"], "data:image/png;base64,BBBB");
+
+        // Assassins are listed like on word boards
+        let template = Language::English.template();
+        assert!(content[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("{}\nB1", template.assassins)));
+
+        // The headers and the clues already given are in the language of the clues
+        let options = GenerateOptions {
+            language: Language::German,
+            used_clues: vec!["meer".to_string()],
+            ..Default::default()
+        };
+        let result = Instance::build_request_body(&board, &"model".to_string(), &options);
+        let text = result["messages"][1]["content"][0]["text"]
+            .as_str()
+            .unwrap();
+        let template = Language::German.template();
+        assert!(text.starts_with(&format!(
+            "{}\nA1\n\n{}\nB1",
+            template.to_link, template.to_avoid
+        )));
+        assert!(text.contains(&format!("{}\nmeer", template.used_clues)));
+        assert!(text.contains(Language::German.template().end_game));
+
+        // Images are counted separately from text
+        assert!(
+            Instance::estimate_prompt_tokens(
+                &board,
+                &"model".to_string(),
+                &GenerateOptions::default()
+            ) > 2 * IMAGE_TOKENS
+        );
+    }
+
+    #[test]
+    fn test_build_request_body_assassins() {
+        let board = Board::new(
+            vec!["link1".to_string()],
+            vec!["avoid1".to_string(), "assassin1".to_string()],
+        )
+        .with_assassins(vec!["assassin1".to_string()]);
+
+        let result =
+            Instance::build_request_body(&board, &"model".to_string(), &GenerateOptions::default());
+        let content = result["messages"][1]["content"].as_str().unwrap();
+        assert!(content.contains(
+            "Assassins (a clue that relates to any of these loses the game):\nassassin1"
+        ));
+    }
+
+    #[test]
+    fn test_build_request_body_language() {
+        let board = Board::new(vec!["schiff".to_string()], vec!["hafen".to_string()])
+            .with_assassins(vec!["hafen".to_string()]);
+        let options = GenerateOptions {
+            language: Language::German,
+            ..Default::default()
+        };
+
+        let result = Instance::build_request_body(&board, &"model".to_string(), &options);
+        let system_prompt = result["messages"][0]["content"].as_str().unwrap();
+        assert!(system_prompt.starts_with("\nDu bist der Geheimdienstchef"));
+        let content = result["messages"][1]["content"].as_str().unwrap();
+        assert!(content.starts_with("Verbinden:\nschiff\n\nVermeiden:\nhafen"));
+        assert!(content.contains("Attentäter"));
+
+        // A single word to link is the end game
+        assert!(system_prompt.contains("mindestens 1 [Agentenwort]"));
+        assert!(content.ends_with("nicht mit einem [Vermeidungswort] verwechselt werden kann."));
+
+        // Clues already given in this game
+        let options = GenerateOptions {
+            used_clues: vec!["meer".to_string()],
+            ..Default::default()
+        };
+        let result = Instance::build_request_body(&board, &"model".to_string(), &options);
+        let content = result["messages"][1]["content"].as_str().unwrap();
+        assert!(content.contains("(never give them again):\nmeer"));
+
+        // Target words
+        let options = GenerateOptions {
+            language: Language::German,
+            targets: vec!["schiff".to_string()],
+            ..Default::default()
+        };
+        let result = Instance::build_request_body(&board, &"model".to_string(), &options);
+        let content = result["messages"][1]["content"].as_str().unwrap();
+        assert!(content.contains("so wenige andere Wörter wie möglich:\nschiff"));
+    }
 }
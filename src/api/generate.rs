@@ -0,0 +1,280 @@
+use std::error::Error;
+
+use super::Instance;
+use crate::board::Board;
+use crate::clues::clue::Clue;
+use crate::clues::clue_collection::ClueCollection;
+use crate::clues::rules::ClueRules;
+use crate::generation::GenerationTable;
+use crate::language::Language;
+use crate::pricing::{format_cost, PriceTable};
+
+/// Settings for `Instance::generate_clues`. Start from `GenerateOptions::default()` and
+/// change what you need, more settings may be added later.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct GenerateOptions {
+    /// Refuse to send requests whose estimated prompt cost exceeds this amount in USD
+    pub max_cost: Option<f64>,
+    /// Prices used to enforce `max_cost`
+    pub prices: PriceTable,
+    /// Language of the prompt, the clues and the table headers
+    pub language: Language,
+    /// Clues already given in this game, which must not be suggested again
+    pub used_clues: Vec<String>,
+    /// Words to link that every clue must cover, for a focused turn
+    pub targets: Vec<String>,
+    /// Sampling settings for each model, like the temperature
+    pub generation: GenerationTable,
+    /// How many clues to ask for and how many words each must link
+    pub rules: ClueRules,
+}
+
+impl GenerateOptions {
+    pub fn max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    pub fn prices(mut self, prices: PriceTable) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn used_clues(mut self, used_clues: Vec<String>) -> Self {
+        self.used_clues = used_clues;
+        self
+    }
+
+    pub fn targets(mut self, targets: Vec<String>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn generation(mut self, generation: GenerationTable) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    pub fn rules(mut self, rules: ClueRules) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+impl Instance {
+    /// Ask each language model for clues on `board` and collect the results
+    pub async fn generate_clues(
+        &self,
+        board: &Board,
+        model_ids: &[String],
+        options: &GenerateOptions,
+    ) -> Result<ClueCollection, Box<dyn Error>> {
+        if model_ids.is_empty() {
+            return Err("No language model selected".into());
+        }
+
+        check_targets(&options.targets, board, options.language)?;
+        check_budget(board, model_ids, options)?;
+
+        // Aggregate responses from each language model
+        let mut responses = vec![];
+        for model_id in model_ids {
+            let response = self.post_chat_completions(board, model_id, options).await?;
+            responses.push(response);
+        }
+
+        // Pictures are referred to by coordinates instead of words
+        let language = options.language;
+        let min_linked = options.rules.for_board(board).min_linked;
+        let clue_collection = if board.pictures().is_empty() {
+            ClueCollection::with_parser(responses, |line, source| {
+                Clue::with_language(line, source, language, min_linked)
+            })
+        } else {
+            ClueCollection::with_parser(responses, |line, source| {
+                Clue::with_coordinates(line, source, language, min_linked, board.to_link())
+            })
+        };
+        let clue_collection = clue_collection
+            .with_language(language)
+            .linking_only(board)
+            .without_clues(&options.used_clues);
+        if options.targets.is_empty() {
+            Ok(clue_collection)
+        } else {
+            Ok(clue_collection.targeting(&options.targets, board))
+        }
+    }
+}
+
+/// Check that every target is one of the words to link, which pictures don't have
+pub fn check_targets(targets: &[String], board: &Board, language: Language) -> Result<(), String> {
+    if targets.is_empty() {
+        return Ok(());
+    }
+    if !board.pictures().is_empty() {
+        return Err("Targets can't be used with Codenames Pictures".to_string());
+    }
+    match targets.iter().find(|target| {
+        !board
+            .to_link()
+            .iter()
+            .any(|word| language.same_word(word, target))
+    }) {
+        Some(target) => Err(format!("Target '{target}' is not one of the words to link")),
+        None => Ok(()),
+    }
+}
+
+/// Estimate the prompt cost of every request and compare the total against the budget of
+/// `options`, so that nothing is sent that may go over it
+pub fn check_budget(
+    board: &Board,
+    model_ids: &[String],
+    options: &GenerateOptions,
+) -> Result<(), String> {
+    let Some(max_cost) = options.max_cost else {
+        return Ok(());
+    };
+
+    let mut estimated_cost = 0.0;
+    for model_id in model_ids {
+        let Some(price) = options.prices.get(model_id) else {
+            return Err(format!(
+                "No known price for {model_id}, so the budget can't be enforced\n\
+                Add it to the [pricing] table of your config file"
+            ));
+        };
+        let prompt_tokens = Instance::estimate_prompt_tokens(board, model_id, options);
+        estimated_cost += prompt_tokens as f64 * price.prompt / 1_000_000.0;
+    }
+
+    if estimated_cost > max_cost {
+        return Err(format!(
+            "Estimated prompt cost {} exceeds the budget of {}",
+            format_cost(estimated_cost),
+            format_cost(max_cost)
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::pictures::PicturesBoard;
+    use httpmock::prelude::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_generate_clues() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions.json");
+        });
+
+        let api_instance = Instance::builder(server.url("/")).build();
+        let board = Board::new(
+            [
+                "sound",
+                "bee",
+                "bond",
+                "tokyo",
+                "scuba diver",
+                "hospital",
+                "walrus",
+            ]
+            .map(String::from)
+            .to_vec(),
+            vec!["park".to_string(), "penny".to_string()],
+        );
+        let model_ids = vec!["model-a".to_string(), "model-b".to_string()];
+
+        // The clue linking words to avoid is discarded
+        let clue_collection = api_instance
+            .generate_clues(&board, &model_ids, &GenerateOptions::default())
+            .await
+            .unwrap();
+        mock.assert_hits(2);
+        assert_eq!(clue_collection.len(), 8);
+
+        // No models
+        assert!(api_instance
+            .generate_clues(&board, &[], &GenerateOptions::default())
+            .await
+            .is_err());
+
+        // Targets must be words to link
+        let options = GenerateOptions {
+            targets: vec!["park".to_string()],
+            ..Default::default()
+        };
+        assert!(api_instance
+            .generate_clues(&board, &model_ids, &options)
+            .await
+            .is_err());
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_generate_clues_pictures() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .body_contains(r#""type":"image_url""#)
+                .body_contains("data:image/png;base64,");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions_pictures.json");
+        });
+
+        let content = fs::read_to_string("resources/tests/pictures/board.pictures").unwrap();
+        let board = PicturesBoard::parse(&content)
+            .unwrap()
+            .to_board(Path::new("resources/tests/pictures"))
+            .unwrap();
+
+        let api_instance = Instance::builder(server.url("/")).build();
+        let clue_collection = api_instance
+            .generate_clues(
+                &board,
+                &["llama-3.2-90b-vision-preview".to_string()],
+                &GenerateOptions::default(),
+            )
+            .await
+            .unwrap();
+        mock.assert();
+
+        // Clues linking a word, a card off the board or another team's card are discarded
+        assert_eq!(clue_collection.len(), 2);
+        let clue = clue_collection.iter().next().unwrap();
+        assert_eq!(clue.clue_word(), "color");
+        assert_eq!(clue.linked_words(), ["A1", "B3", "E4"]);
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let board = Board::new(vec!["sound".to_string()], vec!["park".to_string()]);
+        let gpt = ["gpt-4o".to_string()];
+        let options = GenerateOptions::default();
+        assert!(check_budget(&board, &gpt, &options.clone().max_cost(1.0)).is_ok());
+        assert!(check_budget(&board, &gpt, &options.clone().max_cost(0.0000001)).is_err());
+
+        // Unknown price, which only matters with a budget
+        let local = ["local-model".to_string()];
+        assert!(check_budget(&board, &local, &options.clone().max_cost(1.0)).is_err());
+        assert!(check_budget(&board, &local, &options).is_ok());
+    }
+}
@@ -1,14 +1,25 @@
+use crate::cache::Cache;
 use crate::configs::config::Config;
 use dotenv::dotenv;
 use std::env;
 
 mod chat_completions;
 mod models;
+pub mod secret;
+mod vision;
 
+use secret::Secret;
+
+mod generate;
+
+pub use generate::{check_budget, check_targets, GenerateOptions};
+
+#[derive(Debug)]
 pub struct Instance {
     client: reqwest::Client,
     base_url: String,
-    api_key: String,
+    api_key: Secret,
+    cache: Option<Cache>,
 }
 
 impl Instance {
@@ -18,20 +29,30 @@ impl Instance {
 
         let base_url =
             Self::read_from_env_or_config_file("OPENAI_API_BASE_URL", config.get_base_url())?;
-
-        let base_url = if !base_url.ends_with('/') {
-            format!("{base_url}/")
-        } else {
-            base_url
+        let api_key = match env::var("API_KEY") {
+            Ok(key) => Secret::new(key),
+            Err(_) => secret::resolve_api_key(&config)?.ok_or(
+                "Could not find environment variable 'API_KEY' or any related configuration\n\
+                Please check you config file",
+            )?,
         };
 
-        let api_key = Self::read_from_env_or_config_file("API_KEY", config.get_api_key())?;
+        Ok(Self::builder(base_url).api_key(api_key.expose()).build())
+    }
 
-        Ok(Self {
-            client: reqwest::Client::new(),
-            base_url,
-            api_key,
-        })
+    /// Configure an instance explicitly, without reading the config file or environment variables
+    pub fn builder(base_url: impl Into<String>) -> InstanceBuilder {
+        InstanceBuilder {
+            base_url: base_url.into(),
+            api_key: Secret::default(),
+            client: None,
+            cache: None,
+        }
+    }
+
+    /// Store chat completions responses in `cache` and reuse them for identical requests
+    pub fn set_cache(&mut self, cache: Cache) {
+        self.cache = Some(cache);
     }
 
     fn read_from_env_or_config_file(
@@ -54,6 +75,47 @@ impl Instance {
     }
 }
 
+pub struct InstanceBuilder {
+    base_url: String,
+    api_key: Secret,
+    client: Option<reqwest::Client>,
+    cache: Option<Cache>,
+}
+
+impl InstanceBuilder {
+    /// Leave unset for providers that don't need a key
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Secret::new(api_key);
+        self
+    }
+
+    /// Share an existing HTTP client, e.g. one with custom timeouts or proxies
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn build(self) -> Instance {
+        let base_url = if !self.base_url.ends_with('/') {
+            format!("{}/", self.base_url)
+        } else {
+            self.base_url
+        };
+
+        Instance {
+            client: self.client.unwrap_or_default(),
+            base_url,
+            api_key: self.api_key,
+            cache: self.cache,
+        }
+    }
+}
+
 #[cfg(test)]
 impl Instance {
     pub(crate) fn set_base_url(&mut self, base_url: String) {
@@ -67,7 +129,8 @@ impl Default for Instance {
         Self {
             client: reqwest::Client::new(),
             base_url: "".to_string(),
-            api_key: "".to_string(),
+            api_key: Secret::default(),
+            cache: None,
         }
     }
 }
@@ -83,13 +146,28 @@ mod tests {
 
         let api_instance = Instance::new().unwrap();
         assert_eq!(api_instance.base_url, "abc/");
-        assert_eq!(api_instance.api_key, "def");
+        assert_eq!(api_instance.api_key.expose(), "def");
+    }
+
+    #[test]
+    fn test_builder() {
+        let api_instance = Instance::builder("https://example.com/v1")
+            .api_key("key")
+            .build();
+        assert_eq!(api_instance.base_url, "https://example.com/v1/");
+        assert_eq!(api_instance.api_key.expose(), "key");
+        assert!(api_instance.cache.is_none());
+        assert!(!format!("{api_instance:?}").contains("key\""));
+
+        let api_instance = Instance::builder("https://example.com/v1/").build();
+        assert_eq!(api_instance.base_url, "https://example.com/v1/");
+        assert_eq!(api_instance.api_key.expose(), "");
     }
 
     #[test]
     fn test_default() {
         let api_instance = Instance::default();
         assert_eq!(api_instance.base_url, "");
-        assert_eq!(api_instance.api_key, "");
+        assert_eq!(api_instance.api_key.expose(), "");
     }
 }
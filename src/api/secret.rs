@@ -0,0 +1,147 @@
+use std::fmt;
+use std::fs;
+use std::process::Command;
+
+use crate::configs::config::Config;
+
+/// A string that never shows up in debug output or error messages
+#[derive(Clone, Default, PartialEq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// Get the actual secret. Avoid passing the result to anything that may print it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret([redacted])")
+    }
+}
+
+/// Find the API key from the config file in this order: `key-command`, `key-file`, `key`
+pub(crate) fn resolve_api_key(config: &Config) -> Result<Option<Secret>, String> {
+    if let Some(command) = config.get_api_key_command() {
+        return run_key_command(command).map(Some);
+    }
+
+    if let Some(path) = config.get_api_key_file() {
+        return read_key_file(path).map(Some);
+    }
+
+    Ok(config.get_api_key().map(Secret::new))
+}
+
+pub(crate) fn run_key_command(command: &str) -> Result<Secret, String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("Failed to run 'api.key-command' ({command}): {e}"))?;
+
+    // Only report stderr and the exit status, stdout may contain the key
+    if !output.status.success() {
+        return Err(format!(
+            "'api.key-command' ({command}) exited with {}\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let key = String::from_utf8(output.stdout)
+        .map_err(|_| format!("'api.key-command' ({command}) did not print valid UTF-8"))?;
+    // Tools like `pass` print extra lines after the password
+    let key = key.lines().next().unwrap_or_default().trim();
+    if key.is_empty() {
+        return Err(format!("'api.key-command' ({command}) did not print a key"));
+    }
+
+    Ok(Secret::new(key))
+}
+
+pub(crate) fn read_key_file(path: &str) -> Result<Secret, String> {
+    let key = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read 'api.key-file' ({path}): {}", e.kind()))?;
+
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("'api.key-file' ({path}) is empty"));
+    }
+
+    Ok(Secret::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn config_with(api_table: &str) -> (tempfile::TempDir, Config) {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(&config_file, format!("[api]\n{api_table}\n")).unwrap();
+        let config = Config::load(&config_file).unwrap();
+        (temp_dir, config)
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = Secret::new("sk-very-secret");
+        assert_eq!(format!("{secret:?}"), "Secret([redacted])");
+        assert_eq!(secret.expose(), "sk-very-secret");
+    }
+
+    #[test]
+    fn test_resolve_plaintext_key() {
+        let (_dir, config) = config_with("key = \"plain\"");
+        let key = resolve_api_key(&config).unwrap().unwrap();
+        assert_eq!(key.expose(), "plain");
+
+        let (_dir, config) = config_with("key = \"\"");
+        assert!(resolve_api_key(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_resolve_key_file() {
+        let temp_dir = tempdir().unwrap();
+        let key_file = temp_dir.path().join("openai");
+        fs::write(&key_file, "from-file\n").unwrap();
+
+        let (_dir, config) = config_with(&format!(
+            "key = \"plain\"\nkey-file = '{}'",
+            key_file.display()
+        ));
+        let key = resolve_api_key(&config).unwrap().unwrap();
+        assert_eq!(key.expose(), "from-file");
+
+        let (_dir, config) = config_with("key-file = '/none/existent/path/lol'");
+        assert!(resolve_api_key(&config).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_key_command() {
+        let (_dir, config) = config_with("key-command = \"printf 'from-command\\nextra'\"");
+        let key = resolve_api_key(&config).unwrap().unwrap();
+        assert_eq!(key.expose(), "from-command");
+
+        // The error should never include what the command printed
+        let temp_dir = tempdir().unwrap();
+        let key_file = temp_dir.path().join("openai");
+        fs::write(&key_file, "leaked-secret").unwrap();
+        let (_dir, config) = config_with(&format!(
+            "key-command = \"cat '{}'; exit 3\"",
+            key_file.display()
+        ));
+        let error = resolve_api_key(&config).unwrap_err();
+        assert!(error.contains("exit"));
+        assert!(!error.contains("leaked-secret"));
+    }
+}
@@ -0,0 +1,197 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+
+use super::Instance;
+use crate::board::grid::SIZE;
+use crate::board::pictures::data_url;
+use crate::language::Language;
+
+const EXTRACT_BOARD_PROMPT: &str = "
+You are looking at a photo of a Codenames board: 25 word cards laid out in 5 rows of 5.
+Read the word on every card, row by row from the top left corner, as seen from the side the photo was taken.
+Write each word in lower case, exactly as printed. If a card can't be read, write an empty string.
+";
+
+/// The grid of words, as requested with structured output
+#[derive(Deserialize)]
+struct ExtractedBoard {
+    rows: Vec<Vec<String>>,
+}
+
+impl Instance {
+    /// Read the 25 words of a board from a photo with a vision model, row by row, normalized
+    /// following the rules of `language`. Unreadable cards are empty strings.
+    pub async fn extract_board_words(
+        &self,
+        photo: &Path,
+        model_id: &str,
+        language: Language,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let request_body = Self::build_extract_board_body(&data_url(photo)?, model_id);
+
+        let response = self
+            .send_chat_completions(&request_body)
+            .await
+            .map_err(|e| format!("Failed to send the photo to API server: {e}"))?;
+
+        let Some(choice) = response.choices.first() else {
+            return Err("The language model didn't return anything".into());
+        };
+
+        parse_extracted_board(&choice.message.content, language)
+    }
+
+    fn build_extract_board_body(image_url: &str, model_id: &str) -> serde_json::Value {
+        let row = json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "minItems": SIZE,
+            "maxItems": SIZE
+        });
+
+        json!({
+            "messages": [
+                {
+                    "role": "system",
+                    "content": EXTRACT_BOARD_PROMPT,
+                },
+                {
+                    "role": "user",
+                    "content": [
+                        { "type": "image_url", "image_url": { "url": image_url } }
+                    ]
+                }
+            ],
+            "model": model_id,
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "board",
+                    "strict": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "rows": {
+                                "type": "array",
+                                "items": row,
+                                "minItems": SIZE,
+                                "maxItems": SIZE
+                            }
+                        },
+                        "required": ["rows"],
+                        "additionalProperties": false
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Check the shape of the grid and flatten it
+fn parse_extracted_board(
+    content: &str,
+    language: Language,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    // Some models wrap structured output in a code block anyway
+    let content = content
+        .trim()
+        .trim_start_matches("```json")
+        .trim_matches('`')
+        .trim();
+    let extracted: ExtractedBoard = serde_json::from_str(content)
+        .map_err(|e| format!("The language model didn't return a board: {e}"))?;
+
+    if extracted.rows.len() != SIZE || extracted.rows.iter().any(|row| row.len() != SIZE) {
+        return Err(format!(
+            "The language model didn't find {SIZE} rows of {SIZE} cards. Try a clearer photo"
+        )
+        .into());
+    }
+
+    Ok(extracted
+        .rows
+        .iter()
+        .flatten()
+        .map(|word| language.normalize(word))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{Cache, CacheMode, DEFAULT_TTL};
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_extract_board_words() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .body_contains(r#""response_format""#)
+                .body_contains("data:image/png;base64,");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/extract_board.json");
+        });
+
+        let api_instance = Instance::builder(server.url("/")).build();
+        let words = api_instance
+            .extract_board_words(
+                Path::new("resources/tests/pictures/01.png"),
+                "llama-3.2-90b-vision-preview",
+                Language::English,
+            )
+            .await
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(words.len(), 25);
+        // Spelled as read, with whitespace collapsed
+        assert_eq!(words[0], "Bond");
+        assert_eq!(words[24], "scuba diver");
+        // Unreadable card
+        assert_eq!(words[7], "");
+
+        // The same photo is read from the cache
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut api_instance = Instance::builder(server.url("/")).build();
+        api_instance.set_cache(Cache::with_dir(
+            temp_dir.path(),
+            CacheMode::Enabled,
+            DEFAULT_TTL,
+        ));
+        for _ in 0..2 {
+            api_instance
+                .extract_board_words(
+                    Path::new("resources/tests/pictures/01.png"),
+                    "llama-3.2-90b-vision-preview",
+                    Language::English,
+                )
+                .await
+                .unwrap();
+        }
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn test_parse_extracted_board() {
+        let row = r#"["a", "b", "c", "d", "e"]"#;
+        let rows = [row; 5].join(", ");
+        let content = format!("```json\n{{\"rows\": [{rows}]}}\n```");
+        let english = Language::English;
+        assert_eq!(parse_extracted_board(&content, english).unwrap().len(), 25);
+
+        // Words are normalized in the language of the board
+        let content = content.replacen("\"a\"", "\" Stra\\u00dfe  \"", 1);
+        let words = parse_extracted_board(&content, Language::German).unwrap();
+        assert_eq!(words[0], "Straße");
+
+        // Missing row
+        let rows = [row; 4].join(", ");
+        assert!(parse_extracted_board(&format!("{{\"rows\": [{rows}]}}"), english).is_err());
+
+        assert!(parse_extracted_board("I see a board with 25 words", english).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+
+use super::grid::{check_row, check_rows, lines, write_rows, Line, ParseError, SIZE};
+use super::Board;
+
+/// Timer tokens at the start of a standard game
+pub const DEFAULT_TOKENS: u32 = 9;
+
+/// Each side of the key is seen by one player
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Side {
+    A,
+    B,
+}
+
+impl Side {
+    fn index(self) -> usize {
+        match self {
+            Side::A => 0,
+            Side::B => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DuetRole {
+    Agent,
+    Bystander,
+    Assassin,
+}
+
+impl DuetRole {
+    pub fn marker(self) -> char {
+        match self {
+            DuetRole::Agent => '*',
+            DuetRole::Bystander => '~',
+            DuetRole::Assassin => '!',
+        }
+    }
+
+    fn from_marker(marker: char) -> Option<Self> {
+        match marker {
+            '*' => Some(DuetRole::Agent),
+            '~' => Some(DuetRole::Bystander),
+            '!' => Some(DuetRole::Assassin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DuetCard {
+    pub word: String,
+    /// The role on side A, then on side B
+    pub roles: [DuetRole; 2],
+    pub revealed: bool,
+}
+
+impl DuetCard {
+    pub fn role(&self, side: Side) -> DuetRole {
+        self.roles[side.index()]
+    }
+}
+
+/// A Codenames Duet board with its two-sided key. Every card has two markers, one for each
+/// side: `*` agent, `~` bystander, `!` assassin. A leading `-` marks a covered card.
+///
+/// ```text
+/// side = a
+/// tokens = 9
+/// *~bond | ~~blind | !*angel | -**sound | ~!day
+/// ...
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct DuetKey {
+    /// The side of the player giving clues
+    pub side: Side,
+    /// Timer tokens left, shared by both players
+    pub tokens: u32,
+    /// Row by row, from the top left corner
+    pub cards: Vec<DuetCard>,
+}
+
+impl DuetKey {
+    pub fn parse(content: &str) -> Result<Self, ParseError> {
+        let error = |line: usize, column: usize, message: String| ParseError {
+            line,
+            column,
+            message,
+        };
+
+        let mut side = None;
+        let mut tokens = DEFAULT_TOKENS;
+        let mut cards: Vec<DuetCard> = vec![];
+        let mut rows = 0;
+
+        for (line_number, line) in lines(content) {
+            let cells = match line {
+                Line::Header { key, value, column } => {
+                    if rows > 0 {
+                        return Err(error(
+                            line_number,
+                            column,
+                            format!("'{key}' has to be set before the cards"),
+                        ));
+                    }
+                    match key {
+                        "side" => {
+                            side = Some(match value.to_lowercase().as_str() {
+                                "a" => Side::A,
+                                "b" => Side::B,
+                                _ => {
+                                    return Err(error(
+                                        line_number,
+                                        column,
+                                        format!("unknown side '{value}', expected a or b"),
+                                    ))
+                                }
+                            })
+                        }
+                        "tokens" => {
+                            tokens = value.parse().map_err(|_| {
+                                error(
+                                    line_number,
+                                    column,
+                                    format!("invalid number of timer tokens '{value}'"),
+                                )
+                            })?
+                        }
+                        _ => {
+                            return Err(error(
+                                line_number,
+                                column,
+                                format!("unknown setting '{key}'"),
+                            ))
+                        }
+                    }
+                    continue;
+                }
+                Line::Row(cells) => cells,
+            };
+
+            check_row(line_number, rows, SIZE, &cells)?;
+            rows += 1;
+
+            for (column, cell) in cells {
+                let revealed = cell.starts_with('-');
+                let text = cell.strip_prefix('-').unwrap_or(cell);
+
+                let mut markers = text.chars().map(DuetRole::from_marker);
+                let (Some(Some(role_a)), Some(Some(role_b))) = (markers.next(), markers.next())
+                else {
+                    return Err(error(
+                        line_number,
+                        column,
+                        format!(
+                            "expected two role markers before '{text}', one for each side: * (agent), ~ (bystander) or ! (assassin)"
+                        ),
+                    ));
+                };
+
+                let word = text[2..].trim().to_string();
+                if word.is_empty() {
+                    return Err(error(line_number, column, "empty card".to_string()));
+                }
+                if cards.iter().any(|card| card.word == word) {
+                    return Err(error(
+                        line_number,
+                        column,
+                        format!("'{word}' appears more than once"),
+                    ));
+                }
+
+                cards.push(DuetCard {
+                    word,
+                    roles: [role_a, role_b],
+                    revealed,
+                });
+            }
+        }
+
+        check_rows(content, rows, SIZE)?;
+
+        let Some(side) = side else {
+            return Err(error(
+                1,
+                1,
+                "missing 'side = a' or 'side = b' header".to_string(),
+            ));
+        };
+
+        Ok(Self {
+            side,
+            tokens,
+            cards,
+        })
+    }
+
+    /// Check for the standard key: 9 agents, 3 assassins and 13 bystanders on each side,
+    /// and 15 different agents in total
+    pub fn validate(&self) -> Result<(), String> {
+        for side in [Side::A, Side::B] {
+            let count = |role: DuetRole| {
+                self.cards
+                    .iter()
+                    .filter(|card| card.role(side) == role)
+                    .count()
+            };
+            let (agents, assassins) = (count(DuetRole::Agent), count(DuetRole::Assassin));
+            let bystanders = count(DuetRole::Bystander);
+
+            if agents != 9 || assassins != 3 || bystanders != 13 {
+                return Err(format!(
+                    "Expected 9 agents, 3 assassins and 13 bystanders on side {side:?}, \
+                    found {agents} agents, {assassins} assassins and {bystanders} bystanders"
+                ));
+            }
+        }
+
+        let agents = self
+            .cards
+            .iter()
+            .filter(|card| card.roles.contains(&DuetRole::Agent))
+            .count();
+        if agents != 15 {
+            return Err(format!(
+                "Expected 15 different agents across both sides, found {agents}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The uncovered cards from the point of view of `side`. Its agents are linked,
+    /// everything else is avoided and its assassins must never be linked.
+    pub fn to_board(&self) -> Board {
+        let words = |keep: &dyn Fn(DuetRole) -> bool| -> Vec<String> {
+            self.cards
+                .iter()
+                .filter(|card| !card.revealed && keep(card.role(self.side)))
+                .map(|card| card.word.clone())
+                .collect()
+        };
+
+        Board::new(
+            words(&|role| role == DuetRole::Agent),
+            words(&|role| role != DuetRole::Agent),
+        )
+        .with_assassins(words(&|role| role == DuetRole::Assassin))
+    }
+
+    /// Spend a timer token at the end of a turn, returning how many are left
+    pub fn end_turn(&mut self) -> Result<u32, String> {
+        if self.tokens == 0 {
+            return Err("No timer tokens left, the game is over".to_string());
+        }
+
+        self.tokens -= 1;
+        Ok(self.tokens)
+    }
+}
+
+impl fmt::Display for DuetKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = match self.side {
+            Side::A => "a",
+            Side::B => "b",
+        };
+        writeln!(f, "side = {side}")?;
+        writeln!(f, "tokens = {}", self.tokens)?;
+
+        let cells: Vec<String> = self
+            .cards
+            .iter()
+            .map(|card| {
+                let revealed = if card.revealed { "-" } else { "" };
+                let [role_a, role_b] = card.roles;
+                format!(
+                    "{revealed}{}{}{}",
+                    role_a.marker(),
+                    role_b.marker(),
+                    card.word
+                )
+            })
+            .collect();
+
+        write_rows(f, &cells)
+    }
+}
+
+/// Duet keys are grid files with a `side` header
+pub fn is_duet(content: &str) -> bool {
+    lines(content).any(|(_, line)| matches!(line, Line::Header { key: "side", .. }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse() {
+        let content = fs::read_to_string("examples/duet.board").unwrap();
+        let key = DuetKey::parse(&content).unwrap();
+        assert_eq!(key.side, Side::A);
+        assert_eq!(key.tokens, 9);
+        assert_eq!(key.cards.len(), 25);
+        assert!(key.validate().is_ok());
+
+        // Writing it back produces the same key
+        assert_eq!(DuetKey::parse(&key.to_string()).unwrap(), key);
+        assert!(is_duet(&content));
+        assert!(!is_duet(
+            &fs::read_to_string("examples/grid.board").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_to_board() {
+        let content = fs::read_to_string("examples/duet.board").unwrap();
+        let mut key = DuetKey::parse(&content).unwrap();
+
+        let board = key.to_board();
+        assert_eq!(board.to_link().len(), 9);
+        assert_eq!(board.to_avoid().len(), 16);
+        assert_eq!(board.assassins().len(), 3);
+        assert!(board
+            .assassins()
+            .iter()
+            .all(|word| board.to_avoid().contains(word)));
+
+        // The other side sees different agents
+        key.side = Side::B;
+        let other_board = key.to_board();
+        assert_eq!(other_board.to_link().len(), 9);
+        assert_ne!(other_board.to_link(), board.to_link());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let content = fs::read_to_string("examples/duet.board").unwrap();
+        let error = DuetKey::parse(&content.replace("side = a", "")).unwrap_err();
+        assert!(error.message.contains("side"));
+
+        let row = "** a | ** b | ** c | ** d | ** e\n";
+        let error =
+            DuetKey::parse(&format!("side = a\n{row}*~f | *g | ~~h | ~~i | ~~j\n")).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 7));
+
+        let error = DuetKey::parse("side = c\n").unwrap_err();
+        assert!(error.message.contains("unknown side"));
+    }
+
+    #[test]
+    fn test_end_turn() {
+        let content = fs::read_to_string("examples/duet.board").unwrap();
+        let mut key = DuetKey::parse(&content).unwrap();
+        key.tokens = 1;
+        assert_eq!(key.end_turn(), Ok(0));
+        assert!(key.end_turn().is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::grid::{Card, Grid, Role};
+use super::{normalize_word, Team, MAX_WORDS};
+use crate::language::Language;
+
+/// The word list used when none is given
+pub const DEFAULT_WORDS: &str = include_str!("../../resources/words/default.txt");
+
+/// Normalize a word list, dropping blank lines and duplicates in any case
+pub fn word_list<I, S>(lines: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let language = Language::default();
+    let mut words: Vec<String> = vec![];
+    for word in lines.into_iter().map(|line| normalize_word(line.as_ref())) {
+        if !word.is_empty() && !words.iter().any(|other| language.same_word(other, &word)) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Draw 25 words and deal the roles. The starting team is picked at random and gets 9 agents.
+/// The same seed and word list always produce the same board.
+pub fn generate(words: &[String], team: Team, seed: u64) -> Result<Grid, String> {
+    if words.len() < MAX_WORDS {
+        return Err(format!(
+            "The word list needs at least {MAX_WORDS} different words, found {}",
+            words.len()
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let drawn: Vec<&String> = words.choose_multiple(&mut rng, MAX_WORDS).collect();
+
+    let (starting, other) = if rng.gen_bool(0.5) {
+        (Role::Red, Role::Blue)
+    } else {
+        (Role::Blue, Role::Red)
+    };
+    let mut roles = [vec![starting; 9], vec![other; 8], vec![Role::Neutral; 7]].concat();
+    roles.push(Role::Assassin);
+    roles.shuffle(&mut rng);
+
+    let cards = drawn
+        .into_iter()
+        .zip(roles)
+        .map(|(word, role)| Card {
+            word: word.clone(),
+            role,
+            revealed: false,
+        })
+        .collect();
+
+    Ok(Grid { team, cards })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_list() {
+        assert_eq!(word_list(["Bee", "", "bee ", "park"]), ["Bee", "park"]);
+        assert!(word_list(DEFAULT_WORDS.lines()).len() >= 300);
+    }
+
+    #[test]
+    fn test_generate() {
+        let words = word_list(DEFAULT_WORDS.lines());
+        let grid = generate(&words, Team::Blue, 42).unwrap();
+        assert_eq!(grid.team, Team::Blue);
+        assert_eq!(grid.cards.len(), 25);
+        assert!(grid.validate().is_ok());
+
+        // Seeded
+        assert_eq!(generate(&words, Team::Blue, 42).unwrap(), grid);
+        assert_ne!(generate(&words, Team::Blue, 43).unwrap(), grid);
+
+        assert!(generate(&words[..24], Team::Red, 42).is_err());
+    }
+}
@@ -0,0 +1,439 @@
+use std::fmt;
+
+use super::{Board, Team};
+
+/// A Codenames board is 5 cards by 5 cards
+pub const SIZE: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Role {
+    Red,
+    Blue,
+    Neutral,
+    Assassin,
+}
+
+impl Role {
+    pub fn marker(self) -> char {
+        match self {
+            Role::Red => '*',
+            Role::Blue => '+',
+            Role::Neutral => '~',
+            Role::Assassin => '!',
+        }
+    }
+
+    fn from_marker(marker: char) -> Option<Self> {
+        match marker {
+            '*' => Some(Role::Red),
+            '+' => Some(Role::Blue),
+            '~' => Some(Role::Neutral),
+            '!' => Some(Role::Assassin),
+            _ => None,
+        }
+    }
+
+    /// The agents of a team
+    pub fn of(team: Team) -> Self {
+        match team {
+            Team::Red => Role::Red,
+            Team::Blue => Role::Blue,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Card {
+    pub word: String,
+    pub role: Role,
+    pub revealed: bool,
+}
+
+/// A whole board in one file, with a role marker in front of every word:
+///
+/// ```text
+/// team = red
+/// *bond  | ~blind | +angel   | -*sound | ~day
+/// ...
+/// ```
+///
+/// `*` red agent, `+` blue agent, `~` neutral, `!` assassin. A leading `-` marks a revealed card.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Grid {
+    pub team: Team,
+    /// Row by row, from the top left corner
+    pub cards: Vec<Card>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A meaningful line of a grid file, with comments stripped
+pub(super) enum Line<'a> {
+    /// `key = value`, e.g. `team = red`
+    Header {
+        key: &'a str,
+        value: &'a str,
+        column: usize,
+    },
+    /// Cards separated by `|`, with the column where each one starts
+    Row(Vec<(usize, &'a str)>),
+}
+
+/// Split a grid file into numbered headers and rows, skipping comments and blank lines
+pub(super) fn lines(content: &str) -> impl Iterator<Item = (usize, Line<'_>)> {
+    content.lines().enumerate().filter_map(|(index, raw_line)| {
+        let line = raw_line.split('#').next().unwrap_or_default();
+        let trimmed = line.trim();
+        let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+
+        let parsed = if trimmed.is_empty() {
+            return None;
+        } else if trimmed.starts_with(|c: char| c.is_alphabetic()) && !trimmed.contains('|') {
+            // `team = red`, `team: red` and `team red` are all fine
+            let (key, value) = trimmed
+                .split_once(|c: char| c == '=' || c == ':' || c.is_whitespace())
+                .unwrap_or((trimmed, ""));
+            Line::Header {
+                key: key.trim(),
+                value: value.trim_start_matches([' ', '\t', '=', ':']).trim(),
+                column: indent + 1,
+            }
+        } else {
+            let mut offset = 0;
+            let cells = line
+                .split('|')
+                .map(|cell| {
+                    let leading = cell.chars().take_while(|c| c.is_whitespace()).count();
+                    let column = offset + leading + 1;
+                    offset += cell.chars().count() + 1;
+                    (column, cell.trim())
+                })
+                .collect();
+            Line::Row(cells)
+        };
+
+        Some((index + 1, parsed))
+    })
+}
+
+/// Check the shape of a row, before its cards are parsed
+pub(super) fn check_row(
+    line: usize,
+    rows: usize,
+    expected_rows: usize,
+    cells: &[(usize, &str)],
+) -> Result<(), ParseError> {
+    if rows >= expected_rows {
+        return Err(ParseError {
+            line,
+            column: 1,
+            message: format!("expected {expected_rows} rows, found more"),
+        });
+    }
+    if cells.len() != SIZE {
+        return Err(ParseError {
+            line,
+            column: 1,
+            message: format!(
+                "expected {SIZE} cards separated by '|', found {}",
+                cells.len()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Check the number of rows once the whole file has been read
+pub(super) fn check_rows(
+    content: &str,
+    rows: usize,
+    expected_rows: usize,
+) -> Result<(), ParseError> {
+    if rows != expected_rows {
+        return Err(ParseError {
+            line: content.lines().count() + 1,
+            column: 1,
+            message: format!("expected {expected_rows} rows, found {rows}"),
+        });
+    }
+    Ok(())
+}
+
+impl Grid {
+    pub fn parse(content: &str) -> Result<Self, ParseError> {
+        let (team, cards) = parse_cards(content, SIZE)?;
+        Ok(Self { team, cards })
+    }
+
+    /// Check for the standard distribution: 9 cards for the starting team, 8 for the other,
+    /// 7 neutral cards and 1 assassin
+    pub fn validate(&self) -> Result<(), String> {
+        let count = |role: Role| self.cards.iter().filter(|card| card.role == role).count();
+        let (red, blue) = (count(Role::Red), count(Role::Blue));
+        let (neutral, assassin) = (count(Role::Neutral), count(Role::Assassin));
+
+        let agents_ok = matches!((red, blue), (9, 8) | (8, 9));
+        if !agents_ok || neutral != 7 || assassin != 1 {
+            return Err(format!(
+                "Expected 9 and 8 agents, 7 neutral cards and 1 assassin, \
+                found {red} red, {blue} blue, {neutral} neutral and {assassin} assassin"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The unrevealed cards: your team's words are linked, everything else is avoided
+    pub fn to_board(&self) -> Board {
+        let team_role = Role::of(self.team);
+        let words = |keep: &dyn Fn(Role) -> bool| -> Vec<String> {
+            self.cards
+                .iter()
+                .filter(|card| !card.revealed && keep(card.role))
+                .map(|card| card.word.clone())
+                .collect()
+        };
+
+        Board::new(
+            words(&|role| role == team_role),
+            words(&|role| role != team_role),
+        )
+        .with_assassins(words(&|role| role == Role::Assassin))
+    }
+}
+
+/// Parse a grid file with a team header and one role marker per card
+pub(super) fn parse_cards(
+    content: &str,
+    expected_rows: usize,
+) -> Result<(Team, Vec<Card>), ParseError> {
+    let error = |line: usize, column: usize, message: String| ParseError {
+        line,
+        column,
+        message,
+    };
+
+    let mut team = None;
+    let mut cards: Vec<Card> = vec![];
+    let mut rows = 0;
+
+    for (line_number, line) in lines(content) {
+        let cells = match line {
+            Line::Header { key, value, column } => {
+                // The team header has to come before the cards
+                if key != "team" {
+                    return Err(error(
+                        line_number,
+                        column,
+                        format!("unknown setting '{key}'"),
+                    ));
+                }
+                if rows > 0 {
+                    return Err(error(
+                        line_number,
+                        column,
+                        "the team has to be set before the cards".to_string(),
+                    ));
+                }
+                team = Some(match value.to_lowercase().as_str() {
+                    "red" => Team::Red,
+                    "blue" => Team::Blue,
+                    _ => {
+                        return Err(error(
+                            line_number,
+                            column,
+                            format!("unknown team '{value}', expected red or blue"),
+                        ))
+                    }
+                });
+                continue;
+            }
+            Line::Row(cells) => cells,
+        };
+
+        check_row(line_number, rows, expected_rows, &cells)?;
+        rows += 1;
+
+        for (column, cell) in cells {
+            let revealed = cell.starts_with('-');
+            let text = cell.strip_prefix('-').unwrap_or(cell);
+
+            let Some(role) = text.chars().next().and_then(Role::from_marker) else {
+                return Err(error(
+                    line_number,
+                    column,
+                    format!(
+                        "missing role marker before '{text}', use * (red), + (blue), ~ (neutral) or ! (assassin)"
+                    ),
+                ));
+            };
+
+            let word = text[1..].trim().to_string();
+            if word.is_empty() {
+                return Err(error(line_number, column, "empty card".to_string()));
+            }
+            if cards.iter().any(|card| card.word == word) {
+                return Err(error(
+                    line_number,
+                    column,
+                    format!("'{word}' appears more than once"),
+                ));
+            }
+
+            cards.push(Card {
+                word,
+                role,
+                revealed,
+            });
+        }
+    }
+
+    check_rows(content, rows, expected_rows)?;
+
+    Ok((team.unwrap_or_default(), cards))
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let team = match self.team {
+            Team::Red => "red",
+            Team::Blue => "blue",
+        };
+        writeln!(f, "team = {team}")?;
+
+        let cells: Vec<String> = self
+            .cards
+            .iter()
+            .map(|card| {
+                let revealed = if card.revealed { "-" } else { "" };
+                format!("{revealed}{}{}", card.role.marker(), card.word)
+            })
+            .collect();
+
+        write_rows(f, &cells)
+    }
+}
+
+/// Write cards row by row, lining up the columns
+pub(super) fn write_rows(f: &mut fmt::Formatter<'_>, cells: &[String]) -> fmt::Result {
+    let widths: Vec<usize> = (0..SIZE)
+        .map(|column| {
+            cells
+                .iter()
+                .skip(column)
+                .step_by(SIZE)
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    for row in cells.chunks(SIZE) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:width$}"))
+            .collect();
+        writeln!(f, "{}", line.join(" | ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse() {
+        let content = fs::read_to_string("examples/grid.board").unwrap();
+        let grid = Grid::parse(&content).unwrap();
+        assert_eq!(grid.team, Team::Red);
+        assert_eq!(grid.cards.len(), 25);
+        assert_eq!(
+            grid.cards[0],
+            Card {
+                word: "bond".to_string(),
+                role: Role::Red,
+                revealed: false
+            }
+        );
+        assert!(grid.validate().is_ok());
+
+        // Revealed cards are left out
+        let board = grid.to_board();
+        assert_eq!(board.to_link().len(), 8);
+        assert_eq!(board.to_avoid().len(), 15);
+        assert!(board.to_link().contains(&"scuba diver".to_string()));
+        assert!(board.to_avoid().contains(&"scarecrow".to_string()));
+        assert_eq!(board.assassins(), ["scarecrow"]);
+
+        // Writing it back produces the same grid
+        assert_eq!(Grid::parse(&grid.to_string()).unwrap(), grid);
+
+        // The team header can also be separated by a colon or just a space
+        for header in ["team: blue", "team blue", "  team\tblue"] {
+            let content = content.replacen("team = red", header, 1);
+            assert_eq!(Grid::parse(&content).unwrap().team, Team::Blue);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let row = "*a | *b | *c | *d | *e\n";
+
+        // Missing marker on line 2, column 6
+        let content = format!("{row}~f | g | ~h | ~i | ~j\n");
+        let error = Grid::parse(&content).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 6));
+        assert!(error
+            .to_string()
+            .starts_with("line 2, column 6: missing role marker"));
+
+        // Wrong number of cards
+        let error = Grid::parse("*a | *b\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+
+        // Wrong number of rows
+        let error = Grid::parse(row).unwrap_err();
+        assert!(error.message.contains("expected 5 rows, found 1"));
+
+        // Duplicates
+        let error = Grid::parse("*a | *a | *c | *d | *e\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+
+        // Unknown team
+        let error = Grid::parse("team = green\n").unwrap_err();
+        assert!(error.message.contains("unknown team"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let content = fs::read_to_string("examples/grid.board")
+            .unwrap()
+            .replace("!scarecrow", "~scarecrow");
+        let grid = Grid::parse(&content).unwrap();
+        assert!(grid
+            .validate()
+            .unwrap_err()
+            .contains("8 neutral and 0 assassin"));
+    }
+}
@@ -0,0 +1,295 @@
+use serde::Deserialize;
+
+use crate::language::Language;
+
+use pictures::Picture;
+
+pub mod duet;
+pub mod generator;
+pub mod grid;
+pub mod pictures;
+pub mod review;
+pub mod structured;
+
+/// A Codenames board has 25 cards
+pub const MAX_WORDS: usize = 25;
+
+/// The spymaster's team
+#[derive(Deserialize, clap::ValueEnum, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Team {
+    #[default]
+    Red,
+    Blue,
+}
+
+/// The words on a Codenames board, from the point of view of one spymaster
+#[derive(Clone, Debug, PartialEq)]
+pub struct Board {
+    to_link: Vec<String>,
+    to_avoid: Vec<String>,
+    assassins: Vec<String>,
+    pictures: Vec<Picture>,
+}
+
+impl Board {
+    /// `to_link` holds your team's words. `to_avoid` holds your opponent's words,
+    /// the neutral words and the assassin word.
+    pub fn new(to_link: Vec<String>, to_avoid: Vec<String>) -> Self {
+        Self {
+            to_link,
+            to_avoid,
+            assassins: vec![],
+            pictures: vec![],
+        }
+    }
+
+    /// Mark some of the words to avoid as assassins, which must never be linked.
+    /// Codenames Duet has 3 of them on each side.
+    pub fn with_assassins(mut self, assassins: Vec<String>) -> Self {
+        self.assassins = assassins;
+        self
+    }
+
+    /// Use image cards instead of words, for Codenames Pictures. The words to link and avoid
+    /// are then the coordinates of the pictures.
+    pub fn with_pictures(mut self, pictures: Vec<Picture>) -> Self {
+        self.pictures = pictures;
+        self
+    }
+
+    pub fn to_link(&self) -> &[String] {
+        &self.to_link
+    }
+
+    pub fn to_avoid(&self) -> &[String] {
+        &self.to_avoid
+    }
+
+    pub fn assassins(&self) -> &[String] {
+        &self.assassins
+    }
+
+    pub fn pictures(&self) -> &[Picture] {
+        &self.pictures
+    }
+
+    /// The same board without the cards in `revealed`, e.g. the ones guessed during the game
+    pub fn without(&self, revealed: &[String]) -> Self {
+        let remaining = |words: &[String]| {
+            words
+                .iter()
+                .filter(|word| !revealed.contains(word))
+                .cloned()
+                .collect()
+        };
+
+        Self {
+            to_link: remaining(&self.to_link),
+            to_avoid: remaining(&self.to_avoid),
+            assassins: remaining(&self.assassins),
+            pictures: self
+                .pictures
+                .iter()
+                .filter(|picture| !revealed.contains(&picture.coordinate))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Normalize every word following the rules of `language` and check that the board is
+    /// plausible. Duplicates are removed and reported as warnings, a word that is both to link
+    /// and to avoid is an error.
+    pub fn normalize(&mut self, language: Language) -> Result<Vec<String>, String> {
+        // Coordinates of pictures only need to be uppercase, words keep their spelling and
+        // are compared case folded
+        let pictures = !self.pictures.is_empty();
+        let normalize = |word: &str| {
+            if pictures {
+                word.to_uppercase()
+            } else {
+                language.normalize(word)
+            }
+        };
+        let key = |word: &str| {
+            if pictures {
+                word.to_string()
+            } else {
+                language.fold(word)
+            }
+        };
+
+        let mut warnings = vec![];
+        self.to_link = dedup(&self.to_link, "link", normalize, key, &mut warnings);
+        self.to_avoid = dedup(&self.to_avoid, "avoid", normalize, key, &mut warnings);
+        self.assassins = dedup(&self.assassins, "avoid", normalize, key, &mut vec![]);
+
+        // Assassins are words to avoid too
+        for assassin in &self.assassins {
+            if !self.to_avoid.iter().any(|word| key(word) == key(assassin)) {
+                self.to_avoid.push(assassin.clone());
+            }
+        }
+
+        if self.to_link.is_empty() {
+            return Err("No words to link".to_string());
+        }
+        // Until the game is over, the assassin at least is left
+        if self.to_avoid.is_empty() {
+            return Err("No words to avoid, a board always has the assassin".to_string());
+        }
+        if let Some(word) = self
+            .to_link
+            .iter()
+            .find(|word| self.to_avoid.iter().any(|avoid| key(avoid) == key(word)))
+        {
+            return Err(format!(
+                "'{word}' is both a word to link and a word to avoid"
+            ));
+        }
+
+        let total = self.to_link.len() + self.to_avoid.len();
+        if total > MAX_WORDS {
+            return Err(format!(
+                "A Codenames board has at most {MAX_WORDS} words, found {total}"
+            ));
+        }
+
+        Ok(warnings)
+    }
+}
+
+/// Strip byte order marks, collapse whitespace and apply NFC normalization. Use
+/// `Language::normalize` when the language of the board is known.
+pub fn normalize_word(word: &str) -> String {
+    Language::default().normalize(word)
+}
+
+/// Shortest word that counts when it appears inside the other one
+const MIN_CONTAINED_LEN: usize = 4;
+
+/// Whether two words are spelled alike: one contains the other, or they share the first
+/// 4 letters. This is only about spelling, a clue can evoke a word with a different
+/// spelling too. Short words like `sea` are found inside too many others to count.
+/// Both words are case folded following the rules of `language`.
+pub fn looks_alike(clue_word: &str, word: &str, language: Language) -> bool {
+    let (clue_word, word) = (language.fold(clue_word), language.fold(word));
+    let (shorter, longer) = if clue_word.chars().count() <= word.chars().count() {
+        (&clue_word, &word)
+    } else {
+        (&word, &clue_word)
+    };
+    if shorter == longer
+        || (shorter.chars().count() >= MIN_CONTAINED_LEN && longer.contains(shorter.as_str()))
+    {
+        return true;
+    }
+
+    let common_prefix = clue_word
+        .chars()
+        .zip(word.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    common_prefix >= 4
+}
+
+fn dedup(
+    words: &[String],
+    side: &str,
+    normalize: impl Fn(&str) -> String,
+    key: impl Fn(&str) -> String,
+    warnings: &mut Vec<String>,
+) -> Vec<String> {
+    let mut unique: Vec<String> = vec![];
+    let mut keys: Vec<String> = vec![];
+    for word in words.iter().map(|word| normalize(word)) {
+        if word.is_empty() {
+            continue;
+        }
+        let word_key = key(&word);
+        if keys.contains(&word_key) {
+            warnings.push(format!(
+                "'{word}' appears more than once in the words to {side}"
+            ));
+        } else {
+            keys.push(word_key);
+            unique.push(word);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_word() {
+        assert_eq!(normalize_word("\u{feff}Scuba   Diver\r"), "Scuba Diver");
+        // The spelling is kept for display
+        assert_eq!(normalize_word("Straße"), "Straße");
+        // Decomposed "e" + combining acute accent becomes a single character
+        assert_eq!(normalize_word("cafe\u{301}"), "caf\u{e9}");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut board = Board::new(words(&["Sound", "bee", "sound "]), words(&["park"]));
+        let warnings = board.normalize(Language::English).unwrap();
+        assert_eq!(board.to_link(), words(&["Sound", "bee"]));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'sound'"));
+
+        // Duplicates are found case folded, the first spelling is kept
+        let mut board = Board::new(words(&["Straße", "STRASSE"]), words(&["park"]));
+        let warnings = board.normalize(Language::German).unwrap();
+        assert_eq!(board.to_link(), ["Straße"]);
+        assert_eq!(warnings.len(), 1);
+
+        // Assassins are normalized and avoided
+        let mut board =
+            Board::new(words(&["bee"]), words(&["park"])).with_assassins(words(&["Walrus"]));
+        board.normalize(Language::English).unwrap();
+        assert_eq!(board.assassins(), ["Walrus"]);
+        assert_eq!(board.to_avoid(), ["park", "Walrus"]);
+
+        // Overlap
+        let mut board = Board::new(words(&["Park", "bee"]), words(&["park"]));
+        assert!(board
+            .normalize(Language::English)
+            .unwrap_err()
+            .contains("'Park'"));
+
+        // Too many words
+        let avoid: Vec<String> = (0..25).map(|i| format!("word{i}")).collect();
+        let mut board = Board::new(words(&["bee"]), avoid);
+        assert!(board.normalize(Language::English).is_err());
+
+        // Nothing to link or to avoid
+        let mut board = Board::new(words(&[" "]), words(&["park"]));
+        assert!(board.normalize(Language::English).is_err());
+        let mut board = Board::new(words(&["bee"]), vec![]);
+        assert!(board
+            .normalize(Language::English)
+            .unwrap_err()
+            .contains("No words to avoid"));
+    }
+
+    #[test]
+    fn test_looks_alike() {
+        let english = Language::English;
+        assert!(looks_alike("water", "waterfall", english));
+        assert!(looks_alike("Sea", "sea", english));
+        assert!(!looks_alike("seal", "sea", english));
+        assert!(!looks_alike("research", "sea", english));
+        assert!(looks_alike("firework", "work", english));
+        assert!(looks_alike("music", "musical", english));
+        assert!(looks_alike("planet", "plane", english));
+        assert!(!looks_alike("water", "wave", english));
+        assert!(looks_alike("Straßenbahn", "STRASSE", Language::German));
+    }
+}
@@ -0,0 +1,204 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+use super::grid::{self, Card, ParseError, Role, SIZE};
+use super::{Board, Team};
+
+/// A Codenames Pictures board has 4 rows of 5 cards
+pub const ROWS: usize = 4;
+
+/// An image card, labeled by its coordinate on the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct Picture {
+    /// Column letter and row number, e.g. `A1` for the top left card
+    pub coordinate: String,
+    /// The image as a base64 data URL
+    pub url: String,
+}
+
+/// A Codenames Pictures board. Same as a [`grid::Grid`], but every card is the path
+/// to an image file:
+///
+/// ```text
+/// team = red
+/// *images/cat.png | ~images/moon.jpg | ...
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct PicturesBoard {
+    pub team: Team,
+    /// Row by row, from the top left corner
+    pub cards: Vec<Card>,
+}
+
+impl PicturesBoard {
+    pub fn parse(content: &str) -> Result<Self, ParseError> {
+        let (team, cards) = grid::parse_cards(content, ROWS)?;
+        Ok(Self { team, cards })
+    }
+
+    /// Check for the standard distribution: 8 cards for the starting team, 7 for the other,
+    /// 4 neutral cards and 1 assassin
+    pub fn validate(&self) -> Result<(), String> {
+        let count = |role: Role| self.cards.iter().filter(|card| card.role == role).count();
+        let (red, blue) = (count(Role::Red), count(Role::Blue));
+        let (neutral, assassin) = (count(Role::Neutral), count(Role::Assassin));
+
+        let agents_ok = matches!((red, blue), (8, 7) | (7, 8));
+        if !agents_ok || neutral != 4 || assassin != 1 {
+            return Err(format!(
+                "Expected 8 and 7 agents, 4 neutral cards and 1 assassin, \
+                found {red} red, {blue} blue, {neutral} neutral and {assassin} assassin"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Load the images of the unrevealed cards. Relative paths start from `base_dir`,
+    /// usually the folder of the board file. The cards are referred to by coordinates.
+    pub fn to_board(&self, base_dir: &Path) -> Result<Board, String> {
+        let team_role = Role::of(self.team);
+        let mut to_link = vec![];
+        let mut to_avoid = vec![];
+        let mut assassins = vec![];
+        let mut pictures = vec![];
+
+        for (index, card) in self.cards.iter().enumerate() {
+            if card.revealed {
+                continue;
+            }
+
+            let coordinate = coordinate(index);
+            pictures.push(Picture {
+                coordinate: coordinate.clone(),
+                url: data_url(&base_dir.join(&card.word))?,
+            });
+            if card.role == Role::Assassin {
+                assassins.push(coordinate.clone());
+            }
+            if card.role == team_role {
+                to_link.push(coordinate);
+            } else {
+                to_avoid.push(coordinate);
+            }
+        }
+
+        Ok(Board::new(to_link, to_avoid)
+            .with_assassins(assassins)
+            .with_pictures(pictures))
+    }
+}
+
+/// Label a card by its position, e.g. `A1` for the top left card and `E4` for the bottom right
+pub fn coordinate(index: usize) -> String {
+    let column = (b'A' + (index % SIZE) as u8) as char;
+    format!("{column}{}", index / SIZE + 1)
+}
+
+/// Check that a string refers to a card on the board, e.g. `c3`
+pub fn is_coordinate(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some('A'..='E' | 'a'..='e'), Some('1'..='4'), None)
+    )
+}
+
+/// Pictures boards are grid files whose cards are image files
+pub fn is_pictures(content: &str) -> bool {
+    grid::lines(content).any(|(_, line)| match line {
+        grid::Line::Row(cells) => cells.iter().any(|(_, cell)| mime_type(cell).is_some()),
+        grid::Line::Header { .. } => false,
+    })
+}
+
+fn mime_type(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Encode an image file so that it can be sent inside the request
+pub(crate) fn data_url(path: &Path) -> Result<String, String> {
+    let Some(mime_type) = mime_type(&path.to_string_lossy()) else {
+        return Err(format!(
+            "Unsupported image format: {} (use PNG, JPEG, GIF or WebP)",
+            path.display()
+        ));
+    };
+    let bytes =
+        fs::read(path).map_err(|_| format!("Cannot find image file: {}", path.display()))?;
+
+    Ok(format!(
+        "data:{mime_type};base64,{}",
+        STANDARD.encode(bytes)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = "resources/tests/pictures";
+
+    #[test]
+    fn test_coordinate() {
+        assert_eq!(coordinate(0), "A1");
+        assert_eq!(coordinate(7), "C2");
+        assert_eq!(coordinate(19), "E4");
+        assert!(is_coordinate("C2"));
+        assert!(is_coordinate("e4"));
+        assert!(!is_coordinate("F1"));
+        assert!(!is_coordinate("A5"));
+        assert!(!is_coordinate("A12"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = fs::read_to_string(format!("{FIXTURES}/board.pictures")).unwrap();
+        assert!(is_pictures(&content));
+        assert!(!is_pictures(
+            &fs::read_to_string("examples/grid.board").unwrap()
+        ));
+
+        let board = PicturesBoard::parse(&content).unwrap();
+        assert_eq!(board.cards.len(), 20);
+        assert!(board.validate().is_ok());
+
+        // Five rows is one too many
+        let row = content.lines().last().unwrap();
+        assert!(PicturesBoard::parse(&format!("{content}{row}\n")).is_err());
+    }
+
+    #[test]
+    fn test_to_board() {
+        let content = fs::read_to_string(format!("{FIXTURES}/board.pictures")).unwrap();
+        let board = PicturesBoard::parse(&content)
+            .unwrap()
+            .to_board(Path::new(FIXTURES))
+            .unwrap();
+
+        // One red card is revealed
+        assert_eq!(board.to_link().len(), 7);
+        assert_eq!(board.to_avoid().len(), 12);
+        assert_eq!(board.to_link()[0], "A1");
+        assert_eq!(board.assassins(), ["C2"]);
+        assert_eq!(board.pictures().len(), 19);
+        assert!(board.pictures()[0]
+            .url
+            .starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+        // Missing image
+        assert!(PicturesBoard::parse(&content)
+            .unwrap()
+            .to_board(Path::new("nowhere"))
+            .is_err());
+    }
+}
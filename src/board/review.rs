@@ -0,0 +1,111 @@
+use dialoguer::{Input, MultiSelect, Select};
+use std::error::Error;
+use std::io::{self, IsTerminal};
+
+use super::grid::{Card, Grid, Role, SIZE};
+use super::Team;
+use crate::language::Language;
+
+/// Let the user confirm or correct every word read from a photo, then pick the role
+/// of each card, until the board has the standard distribution. Words are normalized and
+/// compared following the rules of `language`.
+pub fn review(words: &[String], team: Team, language: Language) -> Result<Grid, Box<dyn Error>> {
+    if !io::stdin().is_terminal() {
+        return Err("Reviewing the board needs an interactive terminal".into());
+    }
+
+    // Confirm each card, row by row
+    println!("Press Enter to keep a word, or type the correct one");
+    let mut confirmed: Vec<String> = vec![];
+    for (index, word) in words.iter().enumerate() {
+        let input: String = Input::new()
+            .with_prompt(format!(
+                "Row {}, column {}",
+                index / SIZE + 1,
+                index % SIZE + 1
+            ))
+            .with_initial_text(word)
+            .validate_with(|input: &String| {
+                let input = language.normalize(input);
+                if input.is_empty() {
+                    Err("Every card needs a word".to_string())
+                } else if confirmed
+                    .iter()
+                    .any(|word| language.same_word(word, &input))
+                {
+                    Err(format!("'{input}' is already on the board"))
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+        confirmed.push(language.normalize(&input));
+    }
+
+    let (team_role, opponent_role) = match team {
+        Team::Red => (Role::Red, Role::Blue),
+        Team::Blue => (Role::Blue, Role::Red),
+    };
+
+    // The key card isn't on the photo, so the roles come from the user
+    loop {
+        let mut roles = vec![Role::Neutral; confirmed.len()];
+
+        let agents = MultiSelect::new()
+            .with_prompt("Select your team's agents (9 if your team starts, 8 otherwise)")
+            .items(&confirmed)
+            .interact()?;
+        for &index in &agents {
+            roles[index] = team_role;
+        }
+
+        let remaining: Vec<usize> = (0..confirmed.len())
+            .filter(|index| !agents.contains(index))
+            .collect();
+        let remaining_words: Vec<&String> =
+            remaining.iter().map(|&index| &confirmed[index]).collect();
+        let opponents = MultiSelect::new()
+            .with_prompt("Select your opponent's agents")
+            .items(&remaining_words)
+            .interact()?;
+        for &choice in &opponents {
+            roles[remaining[choice]] = opponent_role;
+        }
+
+        let remaining: Vec<usize> = remaining
+            .into_iter()
+            .enumerate()
+            .filter(|(choice, _)| !opponents.contains(choice))
+            .map(|(_, index)| index)
+            .collect();
+        if remaining.is_empty() {
+            eprintln!("No cards left for the assassin. Let's try again");
+            continue;
+        }
+        let remaining_words: Vec<&String> =
+            remaining.iter().map(|&index| &confirmed[index]).collect();
+        let assassin = Select::new()
+            .with_prompt("Select the assassin. The other cards are neutral")
+            .items(&remaining_words)
+            .interact()?;
+        roles[remaining[assassin]] = Role::Assassin;
+
+        let grid = Grid {
+            team,
+            cards: confirmed
+                .iter()
+                .zip(roles)
+                .map(|(word, role)| Card {
+                    word: word.clone(),
+                    role,
+                    revealed: false,
+                })
+                .collect(),
+        };
+
+        match grid.validate() {
+            Ok(()) => return Ok(grid),
+            Err(e) => eprintln!("{e}. Let's try again"),
+        }
+    }
+}
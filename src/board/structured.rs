@@ -0,0 +1,208 @@
+use serde::Deserialize;
+use std::path::Path;
+
+use super::duet::{self, DuetKey};
+use super::grid::{self, Grid};
+use super::pictures::{self, PicturesBoard};
+use super::{Board, Team};
+
+/// Supported formats of board files
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+    /// A 5x5 grid of words with role markers, see [`Grid`] and [`DuetKey`]
+    Grid,
+    /// A 5x4 grid of image files with role markers, see [`PicturesBoard`]
+    Pictures,
+}
+
+impl Format {
+    /// Guess the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "board" | "grid" => Some(Format::Grid),
+            "pictures" => Some(Format::Pictures),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the content, e.g. when reading from stdin
+    pub fn detect(content: &str) -> Self {
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('{') {
+            Format::Json
+        } else if pictures::is_pictures(content) {
+            Format::Pictures
+        } else if content
+            .lines()
+            .any(|line| line.split('|').count() == grid::SIZE)
+        {
+            Format::Grid
+        } else if content.parse::<toml_edit::DocumentMut>().is_ok() {
+            Format::Toml
+        } else {
+            Format::Yaml
+        }
+    }
+}
+
+/// A board file listing the cards of every role
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoardFile {
+    /// The spymaster's team
+    #[serde(default)]
+    team: Team,
+    #[serde(default)]
+    red: Vec<String>,
+    #[serde(default)]
+    blue: Vec<String>,
+    #[serde(default)]
+    neutral: Vec<String>,
+    #[serde(default)]
+    assassin: OneOrMany,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+    #[default]
+    None,
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(word) => vec![word],
+            OneOrMany::Many(words) => words,
+            OneOrMany::None => vec![],
+        }
+    }
+}
+
+/// Parse a board file. Your team's words are the ones to link, everything else is to be avoided.
+/// Image paths in Pictures boards are relative to `base_dir`.
+pub fn parse(content: &str, format: Format, base_dir: &Path) -> Result<Board, String> {
+    let board_file: BoardFile = match format {
+        Format::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        Format::Toml => toml_edit::de::from_str(content).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml_ng::from_str(content).map_err(|e| e.to_string()),
+        Format::Pictures => {
+            let board =
+                PicturesBoard::parse(content).map_err(|e| format!("Invalid board file: {e}"))?;
+            board
+                .validate()
+                .map_err(|e| format!("Invalid board file: {e}"))?;
+            return board.to_board(base_dir);
+        }
+        Format::Grid if duet::is_duet(content) => {
+            let key = DuetKey::parse(content).map_err(|e| format!("Invalid board file: {e}"))?;
+            key.validate()
+                .map_err(|e| format!("Invalid board file: {e}"))?;
+            if key.tokens == 0 {
+                return Err("No timer tokens left, the game is over".to_string());
+            }
+            return Ok(key.to_board());
+        }
+        Format::Grid => {
+            let grid = Grid::parse(content).map_err(|e| format!("Invalid board file: {e}"))?;
+            grid.validate()
+                .map_err(|e| format!("Invalid board file: {e}"))?;
+            return Ok(grid.to_board());
+        }
+    }
+    .map_err(|e| format!("Invalid board file: {e}"))?;
+
+    let (to_link, opponent) = match board_file.team {
+        Team::Red => (board_file.red, board_file.blue),
+        Team::Blue => (board_file.blue, board_file.red),
+    };
+
+    let assassins = Vec::from(board_file.assassin);
+    let mut to_avoid = opponent;
+    to_avoid.extend(board_file.neutral);
+    to_avoid.extend(assassins.clone());
+
+    if to_link.is_empty() {
+        return Err("Invalid board file: your team has no words".to_string());
+    }
+
+    Ok(Board::new(to_link, to_avoid).with_assassins(assassins))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            Format::from_path(&PathBuf::from("board.JSON")),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            Format::from_path(&PathBuf::from("board.yml")),
+            Some(Format::Yaml)
+        );
+        assert_eq!(Format::from_path(&PathBuf::from("board.txt")), None);
+
+        assert_eq!(Format::detect("  {\"red\": []}"), Format::Json);
+        assert_eq!(Format::detect("red = [\"a\"]"), Format::Toml);
+        assert_eq!(Format::detect("red:\n  - a\n"), Format::Yaml);
+        assert_eq!(Format::detect("*a | ~b | c | d | e\n"), Format::Grid);
+        // A YAML block scalar isn't a grid row
+        assert_eq!(
+            Format::detect("red:\n  - a\nnotes: |\n  first game\n"),
+            Format::Yaml
+        );
+        assert_eq!(Format::detect("*cat.png | ~moon.jpg\n"), Format::Pictures);
+        assert_eq!(
+            Format::from_path(&PathBuf::from("game.board")),
+            Some(Format::Grid)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Board::new(
+            vec!["sound".to_string(), "bee".to_string()],
+            vec![
+                "park".to_string(),
+                "penny".to_string(),
+                "walrus".to_string(),
+            ],
+        )
+        .with_assassins(vec!["walrus".to_string()]);
+
+        let json = r#"{"red": ["sound", "bee"], "blue": ["park"], "neutral": ["penny"], "assassin": "walrus"}"#;
+        assert_eq!(parse(json, Format::Json, Path::new(".")).unwrap(), expected);
+
+        let toml = "red = [\"sound\", \"bee\"]\nblue = [\"park\"]\nneutral = [\"penny\"]\nassassin = [\"walrus\"]\n";
+        assert_eq!(parse(toml, Format::Toml, Path::new(".")).unwrap(), expected);
+
+        let yaml =
+            "team: blue\nblue: [sound, bee]\nred: [park]\nneutral: [penny]\nassassin: walrus\n";
+        assert_eq!(parse(yaml, Format::Yaml, Path::new(".")).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("{\"blue\": [\"park\"]}", Format::Json, Path::new(".")).is_err());
+        assert!(parse(
+            "{\"red\": [\"a\"], \"green\": []}",
+            Format::Json,
+            Path::new(".")
+        )
+        .is_err());
+        assert!(parse("red = ", Format::Toml, Path::new(".")).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Cached responses older than this are ignored, unless configured otherwise
+pub const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CacheMode {
+    /// Serve cached responses when possible
+    Enabled,
+    /// Ignore cached responses but store new ones
+    Refresh,
+}
+
+/// On-disk cache of raw chat completions responses, keyed by the hash of the request body
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    mode: CacheMode,
+}
+
+pub struct CacheStats {
+    pub entries: usize,
+    pub expired: usize,
+    pub bytes: u64,
+}
+
+impl Cache {
+    /// Create a cache in the `cache` folder next to the config file
+    pub fn new(mode: CacheMode, ttl: Duration) -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(config_dir) = dirs::config_dir() else {
+            return Err("No config directory found".into());
+        };
+
+        Ok(Self::with_dir(
+            config_dir.join("mastermind").join("cache"),
+            mode,
+            ttl,
+        ))
+    }
+
+    pub fn with_dir<P: AsRef<Path>>(dir: P, mode: CacheMode, ttl: Duration) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            ttl,
+            mode,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Compute the cache key of a request. The body holds everything that affects the answer:
+    /// the model ID, the prompt, the words and any sampling parameters. The base URL tells
+    /// apart providers serving a model under the same ID.
+    pub fn key(base_url: &str, request_body: &serde_json::Value) -> String {
+        let digest = Sha256::digest(format!("{base_url}\n{request_body}").as_bytes());
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Look up a fresh response. Always misses in refresh mode.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+
+        let path = self.entry_path(key);
+        if self.is_expired(&path) {
+            return None;
+        }
+
+        fs::read_to_string(path).ok()
+    }
+
+    pub fn put(&self, key: &str, response: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(key), response)
+    }
+
+    /// Remove every cached response, returning how many there were
+    pub fn clear(&self) -> io::Result<usize> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            fs::remove_file(entry)?;
+        }
+
+        Ok(entries.len())
+    }
+
+    pub fn stats(&self) -> io::Result<CacheStats> {
+        let mut stats = CacheStats {
+            entries: 0,
+            expired: 0,
+            bytes: 0,
+        };
+
+        for entry in self.entries()? {
+            stats.entries += 1;
+            stats.bytes += fs::metadata(&entry)?.len();
+            if self.is_expired(&entry) {
+                stats.expired += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    fn entries(&self) -> io::Result<Vec<PathBuf>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = vec![];
+        for entry in read_dir {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                entries.push(path);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn is_expired(&self, path: &Path) -> bool {
+        let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+            return true;
+        };
+
+        SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age > self.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_key() {
+        let url = "https://api.groq.com/openai/v1/";
+        let body = json!({"model": "model-a", "messages": []});
+        assert_eq!(Cache::key(url, &body), Cache::key(url, &body.clone()));
+        assert_eq!(Cache::key(url, &body).len(), 64);
+
+        let other_body = json!({"model": "model-b", "messages": []});
+        assert_ne!(Cache::key(url, &body), Cache::key(url, &other_body));
+        assert_ne!(
+            Cache::key(url, &body),
+            Cache::key("http://localhost:11434/v1/", &body)
+        );
+    }
+
+    #[test]
+    fn test_get_put() {
+        let temp_dir = tempdir().unwrap();
+        let cache = Cache::with_dir(temp_dir.path(), CacheMode::Enabled, DEFAULT_TTL);
+
+        assert!(cache.get("abc").is_none());
+        cache.put("abc", "{}").unwrap();
+        assert_eq!(cache.get("abc").as_deref(), Some("{}"));
+
+        // Refresh mode never reads from the cache
+        let cache = Cache::with_dir(temp_dir.path(), CacheMode::Refresh, DEFAULT_TTL);
+        assert!(cache.get("abc").is_none());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let temp_dir = tempdir().unwrap();
+        let cache = Cache::with_dir(temp_dir.path(), CacheMode::Enabled, Duration::ZERO);
+        cache.put("abc", "{}").unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(cache.get("abc").is_none());
+        assert_eq!(cache.stats().unwrap().expired, 1);
+    }
+
+    #[test]
+    fn test_stats_and_clear() {
+        let temp_dir = tempdir().unwrap();
+        let cache = Cache::with_dir(
+            temp_dir.path().join("cache"),
+            CacheMode::Enabled,
+            DEFAULT_TTL,
+        );
+
+        // Missing folder counts as an empty cache
+        assert_eq!(cache.stats().unwrap().entries, 0);
+
+        cache.put("abc", "{}").unwrap();
+        cache.put("def", "{\"a\":1}").unwrap();
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.expired, 0);
+        assert_eq!(stats.bytes, 9);
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.stats().unwrap().entries, 0);
+    }
+}
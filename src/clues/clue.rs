@@ -1,4 +1,12 @@
-pub(crate) struct Clue {
+use serde::{Deserialize, Serialize};
+
+use crate::board::pictures::is_coordinate;
+use crate::clues::rules::ClueRules;
+use crate::language::Language;
+
+/// A clue word and the words from your team it links together
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Clue {
     pub(crate) clue_word: String,
     pub(crate) count: usize,
     pub(crate) linked_words: Vec<String>,
@@ -8,14 +16,82 @@ pub(crate) struct Clue {
 impl Clue {
     /// Create a new instance of `Clue` from a single line of clue out of the API response
     pub fn new(clue_line: &str, source: String) -> Option<Self> {
+        Self::with_language(
+            clue_line,
+            source,
+            Language::English,
+            ClueRules::default().min_linked,
+        )
+    }
+
+    /// Same as `new`, with the normalization and the allowed characters of `language`,
+    /// keeping clues that link at least `min_linked` words. The spelling of the model is kept,
+    /// words are compared case folded later on.
+    pub fn with_language(
+        clue_line: &str,
+        source: String,
+        language: Language,
+        min_linked: usize,
+    ) -> Option<Self> {
+        let mut clue = Self::parse(clue_line, source, language, min_linked, |word| {
+            language.is_word(word)
+        })?;
+        for word in &mut clue.linked_words {
+            *word = language.normalize(word);
+        }
+        Some(clue)
+    }
+
+    /// Create a `Clue` for a Codenames Pictures board, where the linked cards are
+    /// coordinates like `B3` instead of words. Clues linking a card that isn't in
+    /// `to_link` are discarded.
+    pub fn with_coordinates(
+        clue_line: &str,
+        source: String,
+        language: Language,
+        min_linked: usize,
+        to_link: &[String],
+    ) -> Option<Self> {
+        let mut clue = Self::parse(clue_line, source, language, min_linked, is_coordinate)?;
+        for coordinate in &mut clue.linked_words {
+            *coordinate = coordinate.to_uppercase();
+        }
+        if !clue
+            .linked_words
+            .iter()
+            .all(|coordinate| to_link.contains(coordinate))
+        {
+            return None;
+        }
+        Some(clue)
+    }
+
+    /// A clue that didn't come from a language model, e.g. one the spymaster came up with,
+    /// normalized like the clues of the models in `language`
+    pub fn given(clue_word: &str, count: usize, language: Language) -> Self {
+        Self {
+            clue_word: language.normalize(clue_word),
+            count,
+            linked_words: vec![],
+            source: "you".to_string(),
+        }
+    }
+
+    fn parse(
+        clue_line: &str,
+        source: String,
+        language: Language,
+        min_linked: usize,
+        is_card: impl Fn(&str) -> bool,
+    ) -> Option<Self> {
         let chunks: Vec<String> = clue_line.split(",").map(|s| s.trim().to_string()).collect();
 
-        // Discard empty lines as well as clues with only one word linked
-        if chunks.len() < 4 {
+        // Discard empty lines as well as clues linking too few words
+        if chunks.len() < 2 + min_linked.max(1) {
             return None;
         }
 
-        let clue_word = chunks[0].to_string();
+        let clue_word = language.normalize(&chunks[0]);
 
         let Ok(count) = chunks[1].parse::<usize>() else {
             return None;
@@ -29,14 +105,9 @@ impl Clue {
         }
 
         // Discard clues that contains special characters (likely due to hallucination)
-        if !clue_word.chars().all(|c| c.is_alphabetic() || c == ' ') {
+        if !language.is_word(&clue_word) || !linked_words.iter().all(|word| is_card(word)) {
             return None;
         }
-        for word in &linked_words {
-            if !word.chars().all(|c| c.is_alphabetic() || c == ' ') {
-                return None;
-            }
-        }
 
         Some(Self {
             clue_word,
@@ -45,6 +116,24 @@ impl Clue {
             source,
         })
     }
+
+    pub fn clue_word(&self) -> &str {
+        &self.clue_word
+    }
+
+    /// The number of words this clue links together
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn linked_words(&self) -> &[String] {
+        &self.linked_words
+    }
+
+    /// The language model that came up with this clue
+    pub fn source(&self) -> &str {
+        &self.source
+    }
 }
 
 #[cfg(test)]
@@ -94,5 +183,93 @@ mod tests {
             String::from("some_source"),
         );
         assert!(clue.is_none());
+
+        // Clues for a single word are only kept in the end game
+        assert!(Clue::new("diver, 1, scuba diver", String::from("some_source")).is_none());
+        let clue = Clue::with_language(
+            "diver, 1, scuba diver",
+            String::from("some_source"),
+            Language::English,
+            1,
+        )
+        .unwrap();
+        assert_eq!(clue.linked_words, ["scuba diver"]);
+    }
+
+    #[test]
+    fn test_with_coordinates() {
+        let to_link = vec!["A1".to_string(), "B3".to_string(), "C5".to_string()];
+        let clue = Clue::with_coordinates(
+            "ocean, 2, b3, A1",
+            String::from("some_source"),
+            Language::English,
+            2,
+            &to_link,
+        )
+        .unwrap();
+        assert_eq!(clue.clue_word, "ocean");
+        assert_eq!(clue.linked_words, vec!["B3".to_string(), "A1".to_string()]);
+
+        // Words, coordinates off the board and cards not to link are discarded
+        let english = Language::English;
+        let parse =
+            |line: &str| Clue::with_coordinates(line, String::from("s"), english, 2, &to_link);
+        assert!(parse("ocean, 2, whale, A1").is_none());
+        assert!(parse("ocean, 2, F1, A1").is_none());
+        assert!(parse("ocean, 2, D4, A1").is_none());
+        assert!(Clue::new("ocean, 2, B3, A1", String::from("s")).is_none());
+    }
+
+    #[test]
+    fn test_given() {
+        let clue = Clue::given(" \u{feff}IŞIK  ", 2, Language::Turkish);
+        assert_eq!(clue.clue_word, "IŞIK");
+        assert_eq!(clue.source, "you");
+
+        // Compared with the clues of the models in the same language
+        let model_clue = Clue::with_language(
+            "ışık, 2, güneş, ay",
+            String::from("s"),
+            Language::Turkish,
+            2,
+        )
+        .unwrap();
+        assert!(Language::Turkish.same_word(&clue.clue_word, &model_clue.clue_word));
+    }
+
+    #[test]
+    fn test_with_language() {
+        // Hyphenated German compounds
+        let clue = Clue::with_language(
+            "See-Fahrt, 2, Schiff, Hafen",
+            String::from("s"),
+            Language::German,
+            2,
+        )
+        .unwrap();
+        assert_eq!(clue.clue_word, "See-Fahrt");
+        assert_eq!(clue.linked_words, vec!["Schiff", "Hafen"]);
+        assert!(Clue::new("see-fahrt, 2, schiff, hafen", String::from("s")).is_none());
+
+        // French elisions
+        let clue = Clue::with_language(
+            "l'eau, 2, mer, pluie",
+            String::from("s"),
+            Language::French,
+            2,
+        )
+        .unwrap();
+        assert_eq!(clue.clue_word, "l'eau");
+
+        // Turkish dotless i
+        let clue = Clue::with_language(
+            "IŞIK, 2, güneş, ay",
+            String::from("s"),
+            Language::Turkish,
+            2,
+        )
+        .unwrap();
+        assert_eq!(clue.clue_word, "IŞIK");
+        assert!(Language::Turkish.same_word(&clue.clue_word, "ışık"));
     }
 }
@@ -2,69 +2,207 @@ use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Table};
 
+use serde::{Serialize, Serializer};
+use std::time::Duration;
+
+use crate::board::{looks_alike, Board};
 use crate::clues::clue::Clue;
 use crate::json::chat_completions::{ChatCompletionsResponse, Usage};
+use crate::language::Language;
+use crate::pricing::{format_cost, PriceTable};
 
+#[derive(Serialize)]
 pub struct ClueCollection {
     clues: Vec<Clue>,
-    usage: Usage,
+    usage: Vec<ModelUsage>,
+    /// Language of the table headers
+    #[serde(skip)]
+    language: Language,
+}
+
+/// Token usage and timing of a single language model
+#[derive(Serialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub usage: Usage,
+    #[serde(serialize_with = "serialize_millis")]
+    pub latency: Duration,
+    /// Whether every response from this model came from the cache
+    pub cached: bool,
+}
+
+/// Write durations as whole milliseconds in machine-readable output
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
 }
 
 impl ClueCollection {
     /// Create a new instance of `ClueCollection` from `Vec<ChatCompletionsResponse>`
     pub fn new(responses: Vec<ChatCompletionsResponse>) -> Self {
-        let mut clues: Vec<Clue> = vec![];
+        Self::with_parser(responses, Clue::new)
+    }
 
-        let mut usage = Usage {
-            prompt_tokens: 0,
-            completion_tokens: 0,
-            total_tokens: 0,
-        };
+    /// Same as `new`, but with a different way of reading each line of clue,
+    /// e.g. `Clue::with_coordinates` for Codenames Pictures
+    pub fn with_parser(
+        responses: Vec<ChatCompletionsResponse>,
+        parser: impl Fn(&str, String) -> Option<Clue>,
+    ) -> Self {
+        let mut clues: Vec<Clue> = vec![];
+        let mut usage: Vec<ModelUsage> = vec![];
 
         // Aggregate clues and token usage information
         for response in responses {
             for choice in response.choices {
                 for line in choice.message.content.lines() {
-                    if let Some(clue) = Clue::new(line.trim(), response.model.clone()) {
+                    if let Some(clue) = parser(line.trim(), response.model.clone()) {
                         clues.push(clue);
                     }
                 }
             }
-            usage.prompt_tokens += response.usage.prompt_tokens;
-            usage.completion_tokens += response.usage.completion_tokens;
-            usage.total_tokens += response.usage.total_tokens;
+
+            // Keep token usage separate for each model
+            match usage.iter_mut().find(|entry| entry.model == response.model) {
+                Some(entry) => {
+                    entry.usage.prompt_tokens += response.usage.prompt_tokens;
+                    entry.usage.completion_tokens += response.usage.completion_tokens;
+                    entry.usage.total_tokens += response.usage.total_tokens;
+                    entry.latency += response.timing.latency;
+                    entry.cached &= response.timing.cached;
+                }
+                None => usage.push(ModelUsage {
+                    model: response.model,
+                    usage: response.usage,
+                    latency: response.timing.latency,
+                    cached: response.timing.cached,
+                }),
+            }
         }
 
         // Sort the clues by the number of words they link together
+        #[allow(clippy::unnecessary_sort_by)]
         clues.sort_by(|a, b| b.count.cmp(&a.count));
 
         // Return
-        Self { clues, usage }
+        Self {
+            clues,
+            usage,
+            language: Language::default(),
+        }
+    }
+
+    /// Collect clues that were already parsed, e.g. from the history. There is no token usage.
+    pub fn from_clues(clues: Vec<Clue>) -> Self {
+        Self {
+            clues,
+            usage: vec![],
+            language: Language::default(),
+        }
+    }
+
+    /// Print the table headers in `language`
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Drop the clues that were already given in this game, compared in the language of the clues
+    pub fn without_clues(mut self, used_clues: &[String]) -> Self {
+        let used_clues: Vec<String> = used_clues
+            .iter()
+            .map(|clue_word| self.language.fold(clue_word.trim()))
+            .collect();
+        self.clues
+            .retain(|clue| !used_clues.contains(&self.language.fold(&clue.clue_word)));
+        self
+    }
+
+    /// Drop the clues linking a word that isn't one of the words to link on `board`,
+    /// such as an assassin or a word the language model made up. Linked words are compared
+    /// case folded and spelled like on the board.
+    pub fn linking_only(mut self, board: &Board) -> Self {
+        let language = self.language;
+        let on_board = |word: &String| {
+            board
+                .to_link()
+                .iter()
+                .find(|board_word| language.same_word(board_word, word))
+                .cloned()
+        };
+        self.clues = self
+            .clues
+            .into_iter()
+            .filter_map(|mut clue| {
+                clue.linked_words = clue
+                    .linked_words
+                    .iter()
+                    .map(on_board)
+                    .collect::<Option<_>>()?;
+                Some(clue)
+            })
+            .collect();
+        self
+    }
+
+    /// Keep the clues that link every target word. The ones spelled like a word to avoid
+    /// come last, the assassins first among them, then the ones linking other words as well.
+    pub fn targeting(mut self, targets: &[String], board: &Board) -> Self {
+        let language = self.language;
+        let targets: Vec<String> = targets.iter().map(|word| language.fold(word)).collect();
+        self.clues.retain(|clue| {
+            let linked_words: Vec<String> = clue
+                .linked_words
+                .iter()
+                .map(|word| language.fold(word))
+                .collect();
+            targets.iter().all(|target| linked_words.contains(target))
+        });
+
+        // Fewest risky words first, the order by count is kept otherwise
+        self.clues.sort_by_key(|clue| {
+            let evokes = |words: &[String]| {
+                words
+                    .iter()
+                    .filter(|word| looks_alike(&clue.clue_word, word, language))
+                    .count()
+            };
+            let extra_words = clue.linked_words.len().saturating_sub(targets.len());
+            (
+                evokes(board.assassins()),
+                evokes(board.to_avoid()),
+                extra_words,
+            )
+        });
+        self
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
     }
 
     pub fn is_empty(&self) -> bool {
         self.clues.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.clues.len()
+    }
+
+    /// Iterate over the clues, from the one linking the most words to the one linking the least
+    pub fn iter(&self) -> std::slice::Iter<'_, Clue> {
+        self.clues.iter()
+    }
+
     fn generate_table(&self) -> Table {
         let mut table = Table::new();
 
         // Set up header and styles
         table
-            .set_header(vec![
-                Cell::new("Clue")
+            .set_header(self.language.table_headers().map(|header| {
+                Cell::new(header)
                     .add_attribute(Attribute::Bold)
-                    .set_alignment(CellAlignment::Center),
-                Cell::new("Count")
-                    .add_attribute(Attribute::Bold)
-                    .set_alignment(CellAlignment::Center),
-                Cell::new("Linked Words")
-                    .add_attribute(Attribute::Bold)
-                    .set_alignment(CellAlignment::Center),
-                Cell::new("Source")
-                    .add_attribute(Attribute::Bold)
-                    .set_alignment(CellAlignment::Center),
-            ])
+                    .set_alignment(CellAlignment::Center)
+            }))
             .set_content_arrangement(ContentArrangement::Dynamic)
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS);
@@ -88,19 +226,134 @@ impl ClueCollection {
         table
     }
 
-    pub fn display_token_info(&self) {
+    pub fn usage(&self) -> &[ModelUsage] {
+        &self.usage
+    }
+
+    fn generate_usage_table(&self, prices: &PriceTable) -> Table {
+        let mut table = Table::new();
+
+        // Set up header and styles
+        table
+            .set_header(
+                [
+                    "Model",
+                    "Prompt Tokens",
+                    "Completion Tokens",
+                    "Total Tokens",
+                    "Cost",
+                ]
+                .map(|header| {
+                    Cell::new(header)
+                        .add_attribute(Attribute::Bold)
+                        .set_alignment(CellAlignment::Center)
+                }),
+            )
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS);
+
+        // Add a row for each model, and then the totals
+        let mut total = Usage::default();
+        let mut total_cost = Some(0.0);
+        for entry in &self.usage {
+            // Cached answers cost nothing this time, and don't count towards the totals
+            if entry.cached {
+                table.add_row(vec![
+                    entry.model.clone(),
+                    entry.usage.prompt_tokens.to_string(),
+                    entry.usage.completion_tokens.to_string(),
+                    entry.usage.total_tokens.to_string(),
+                    "cached".to_string(),
+                ]);
+                continue;
+            }
+
+            let cost = prices.cost(&entry.model, &entry.usage);
+            table.add_row(vec![
+                entry.model.clone(),
+                entry.usage.prompt_tokens.to_string(),
+                entry.usage.completion_tokens.to_string(),
+                entry.usage.total_tokens.to_string(),
+                cost.map_or("unknown".to_string(), format_cost),
+            ]);
+
+            total.prompt_tokens += entry.usage.prompt_tokens;
+            total.completion_tokens += entry.usage.completion_tokens;
+            total.total_tokens += entry.usage.total_tokens;
+            total_cost = total_cost.zip(cost).map(|(a, b)| a + b);
+        }
+
+        if self.usage.len() > 1 {
+            table.add_row(vec![
+                Cell::new("Total").add_attribute(Attribute::Bold),
+                Cell::new(total.prompt_tokens),
+                Cell::new(total.completion_tokens),
+                Cell::new(total.total_tokens),
+                Cell::new(total_cost.map_or("unknown".to_string(), format_cost)),
+            ]);
+        }
+
+        // Right-align the numbers
+        for index in 1..5 {
+            table
+                .column_mut(index)
+                .expect("The table should have 5 columns")
+                .set_cell_alignment(CellAlignment::Right);
+        }
+
+        table
+    }
+
+    fn generate_timing_table(&self) -> Table {
+        let mut table = Table::new();
+
+        // Set up header and styles
+        table
+            .set_header(["Model", "Latency", "Source"].map(|header| {
+                Cell::new(header)
+                    .add_attribute(Attribute::Bold)
+                    .set_alignment(CellAlignment::Center)
+            }))
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS);
+
+        for entry in &self.usage {
+            table.add_row(vec![
+                entry.model.clone(),
+                format!("{:.2}s", entry.latency.as_secs_f64()),
+                if entry.cached { "cache" } else { "API" }.to_string(),
+            ]);
+        }
+
+        // Right-align the durations
+        table
+            .column_mut(1)
+            .expect("The table should have 3 columns")
+            .set_cell_alignment(CellAlignment::Right);
+
+        table
+    }
+
+    pub fn display_token_info(&self, prices: &PriceTable) {
         eprintln!(
-            "\nToken Usage:\n\
-            ----------------------\n\
-            Prompt Tokens: {}\n\
-            Completion Tokens: {}\n\
-            ----------------------\n\
-            Total Tokens: {}",
-            self.usage.prompt_tokens, self.usage.completion_tokens, self.usage.total_tokens
+            "\nToken Usage:\n{}\n\nLatency:\n{}",
+            self.generate_usage_table(prices),
+            self.generate_timing_table()
         );
     }
 }
 
+impl<'a> IntoIterator for &'a ClueCollection {
+    type Item = &'a Clue;
+    type IntoIter = std::slice::Iter<'a, Clue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl std::fmt::Display for ClueCollection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.generate_table())
@@ -129,6 +382,7 @@ mod tests {
     use super::*;
 
     use crate::api::Instance;
+    use crate::board::Board;
     use httpmock::prelude::*;
     use std::fs;
     #[tokio::test]
@@ -150,7 +404,11 @@ mod tests {
 
         // Get responses from mock server
         let responses = vec![api_instance
-            .post_chat_completions(&Vec::<String>::new(), &Vec::<String>::new(), &String::new())
+            .post_chat_completions(
+                &Board::new(vec![], vec![]),
+                &String::new(),
+                &crate::api::GenerateOptions::default(),
+            )
             .await
             .unwrap()];
         mock.assert();
@@ -161,4 +419,128 @@ mod tests {
             fs::read_to_string("resources/tests/expected_outputs/chat_completions.txt").unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_usage_per_model() {
+        let response = |model: &str, prompt_tokens: usize| -> ChatCompletionsResponse {
+            serde_json::from_value(serde_json::json!({
+                "model": model,
+                "choices": [],
+                "usage": {
+                    "prompt_tokens": prompt_tokens,
+                    "completion_tokens": 10,
+                    "total_tokens": prompt_tokens + 10
+                }
+            }))
+            .unwrap()
+        };
+
+        let clue_collection = ClueCollection::new(vec![
+            response("model-a", 100),
+            response("model-b", 200),
+            response("model-a", 50),
+        ]);
+
+        let usage = clue_collection.usage();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].model, "model-a");
+        assert_eq!(usage[0].usage.prompt_tokens, 150);
+        assert_eq!(usage[0].usage.total_tokens, 170);
+        assert_eq!(usage[1].model, "model-b");
+        assert_eq!(usage[1].usage.completion_tokens, 10);
+
+        // Usage is part of the machine-readable output
+        let json = serde_json::to_value(&usage[0]).unwrap();
+        assert_eq!(json["usage"]["prompt_tokens"], 150);
+        assert_eq!(json["latency"], 0);
+        assert_eq!(json["cached"], false);
+
+        // Unknown models don't have a price
+        let table = clue_collection
+            .generate_usage_table(&PriceTable::default())
+            .to_string();
+        assert!(table.contains("unknown"));
+        assert!(table.contains("Total"));
+
+        // Cached answers are free and left out of the totals
+        let mut clue_collection = clue_collection;
+        clue_collection.usage[1].cached = true;
+        let table = clue_collection
+            .generate_usage_table(&PriceTable::default())
+            .to_string();
+        assert!(table.contains("cached"));
+        assert!(!table.contains("380"));
+
+        // Localized headers
+        let table = clue_collection.with_language(Language::German).to_string();
+        assert!(table.contains("Verbundene Wörter"));
+    }
+
+    #[test]
+    fn test_without_clues() {
+        let response = serde_json::from_value(serde_json::json!({
+            "model": "model",
+            "choices": [{ "message": { "content": "music, 2, sound, bee\nfilm, 2, bond, tokyo" } }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+        .unwrap();
+
+        let clue_collection =
+            ClueCollection::new(vec![response]).without_clues(&[" Music ".to_string()]);
+        assert_eq!(clue_collection.len(), 1);
+        assert_eq!(clue_collection.iter().next().unwrap().clue_word(), "film");
+    }
+
+    #[test]
+    fn test_linking_only() {
+        let response = serde_json::from_value(serde_json::json!({
+            "model": "model",
+            "choices": [{ "message": { "content": "music, 2, sound, Bee\nspy, 2, bond, walrus\nfilm, 2, bond, tokyo" } }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+        .unwrap();
+        let board = Board::new(
+            vec!["sound".into(), "bee".into(), "bond".into()],
+            vec!["park".into()],
+        )
+        .with_assassins(vec!["walrus".into()]);
+
+        // Assassins and words that aren't on the board are never linked
+        let clue_collection = ClueCollection::new(vec![response]).linking_only(&board);
+        let clue_words: Vec<&str> = clue_collection
+            .iter()
+            .map(|clue| clue.clue_word())
+            .collect();
+        assert_eq!(clue_words, ["music"]);
+        // Linked words are spelled like on the board
+        assert_eq!(clue_collection.clues[0].linked_words(), ["sound", "bee"]);
+    }
+
+    #[test]
+    fn test_targeting() {
+        let response = serde_json::from_value(serde_json::json!({
+            "model": "model",
+            "choices": [{ "message": { "content": "musical, 2, sound, bee\nfilm, 2, bond, tokyo\nhum, 3, sound, Bee, drum\nbuzz, 2, bee, sound" } }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+        .unwrap();
+        let board = Board::new(
+            vec![
+                "sound".into(),
+                "bee".into(),
+                "drum".into(),
+                "bond".into(),
+                "tokyo".into(),
+            ],
+            vec!["music".into(), "park".into()],
+        );
+
+        let clue_collection = ClueCollection::new(vec![response])
+            .targeting(&["Bee".to_string(), "sound".to_string()], &board);
+        let clue_words: Vec<&str> = clue_collection
+            .iter()
+            .map(|clue| clue.clue_word())
+            .collect();
+        assert_eq!(clue_words, ["buzz", "hum", "musical"]);
+    }
 }
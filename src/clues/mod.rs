@@ -1,2 +1,3 @@
-pub(crate) mod clue;
+pub mod clue;
 pub mod clue_collection;
+pub mod rules;
@@ -0,0 +1,122 @@
+use crate::board::Board;
+
+/// How many clues to ask for, and how many words each clue has to link
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClueRules {
+    pub min_clues: usize,
+    pub max_clues: usize,
+    /// Clues linking fewer words are discarded
+    pub min_linked: usize,
+    /// With this many words to link left or fewer, clues for a single word are fine.
+    /// 0 turns the end game off.
+    pub end_game: usize,
+}
+
+impl Default for ClueRules {
+    fn default() -> Self {
+        Self {
+            min_clues: 5,
+            max_clues: 10,
+            min_linked: 2,
+            end_game: 2,
+        }
+    }
+}
+
+impl ClueRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_clues == 0 {
+            return Err("The minimum number of clues must be at least 1".to_string());
+        }
+        if self.max_clues < self.min_clues {
+            return Err(format!(
+                "The maximum number of clues ({}) is below the minimum ({})",
+                self.max_clues, self.min_clues
+            ));
+        }
+        if self.min_linked == 0 {
+            return Err("Clues must link at least 1 word".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Whether only a few words to link are left on `board`
+    pub fn is_end_game(&self, board: &Board) -> bool {
+        board.to_link().len() <= self.end_game
+    }
+
+    /// The rules for `board`. A clue can't link more words than are left, and in the
+    /// end game a safe clue for a single word is often the right move.
+    pub fn for_board(&self, board: &Board) -> Self {
+        let min_linked = if self.is_end_game(board) {
+            1
+        } else {
+            self.min_linked.min(board.to_link().len()).max(1)
+        };
+
+        Self {
+            min_linked,
+            ..*self
+        }
+    }
+
+    /// Fill in the `{min_clues}`, `{max_clues}` and `{min_linked}` placeholders of a prompt
+    pub fn fill(&self, prompt: &str) -> String {
+        prompt
+            .replace("{min_clues}", &self.min_clues.to_string())
+            .replace("{max_clues}", &self.max_clues.to_string())
+            .replace("{min_linked}", &self.min_linked.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(to_link: &[&str]) -> Board {
+        Board::new(
+            to_link.iter().map(|word| word.to_string()).collect(),
+            vec!["park".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_for_board() {
+        let rules = ClueRules::default();
+        assert_eq!(rules.for_board(&board(&["a", "b", "c"])), rules);
+
+        // End game
+        let end_game = rules.for_board(&board(&["a", "b"]));
+        assert!(rules.is_end_game(&board(&["a", "b"])));
+        assert_eq!(end_game.min_linked, 1);
+        assert_eq!(
+            rules.fill("at least {min_linked}, {min_clues}-{max_clues}"),
+            "at least 2, 5-10"
+        );
+
+        // Without the end game, clues can't link more words than are left
+        let rules = ClueRules {
+            min_linked: 3,
+            end_game: 0,
+            ..Default::default()
+        };
+        assert_eq!(rules.for_board(&board(&["a", "b"])).min_linked, 2);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ClueRules::default().validate().is_ok());
+        let rules = ClueRules {
+            min_clues: 6,
+            max_clues: 3,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err());
+        let rules = ClueRules {
+            min_linked: 0,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::error::Error;
+use std::process;
+
+use crate::api::{secret, Instance};
+use crate::configs::config::Config;
+use crate::configs::setup;
+use crate::model_collection::ModelCollection;
+use crate::{split_list, ConfigCommand};
+
+/// Carry out a `mastermind config` subcommand
+pub async fn run(command: &ConfigCommand) -> Result<(), Box<dyn Error>> {
+    let mut config = Config::new()?;
+
+    match command {
+        ConfigCommand::Get { key } => match config.get(key)? {
+            Some(value) => println!("{value}"),
+            None => return Err(format!("'{key}' is not set").into()),
+        },
+        ConfigCommand::Set { key, value } => {
+            config.set(key, value)?;
+            config.save(config.path())?;
+        }
+        ConfigCommand::Unset { key } => {
+            config.unset(key)?;
+            config.save(config.path())?;
+        }
+        ConfigCommand::List => {
+            for (key, value) in config.list() {
+                println!("{key} = {}", value.unwrap_or_default());
+            }
+        }
+        ConfigCommand::Init => setup::wizard(&mut config).await?,
+        ConfigCommand::Path => println!("{}", config.path().display()),
+        ConfigCommand::Edit => edit(&config)?,
+        ConfigCommand::Doctor => doctor(&config).await?,
+    }
+
+    Ok(())
+}
+
+fn edit(config: &Config) -> Result<(), Box<dyn Error>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    // Allow editors with arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or("No editor configured")?;
+    let status = process::Command::new(program)
+        .args(parts)
+        .arg(config.path())
+        .status()
+        .map_err(|e| format!("Failed to launch editor '{program}': {e}"))?;
+
+    if !status.success() {
+        return Err(format!("Editor '{program}' exited with {status}").into());
+    }
+
+    // Make sure the edited file is still valid
+    let config = Config::load(config.path())?;
+    let errors = config.pricing_errors();
+    if !errors.is_empty() {
+        return Err(format!("Invalid prices, they are ignored:\n{}", errors.join("\n")).into());
+    }
+    Ok(())
+}
+
+async fn doctor(config: &Config) -> Result<(), Box<dyn Error>> {
+    println!("Config file: {}", config.path().display());
+    let mut failed = false;
+    let mut fail = |message: String| {
+        println!("[FAIL] {message}");
+        failed = true;
+    };
+
+    let base_url = env::var("OPENAI_API_BASE_URL")
+        .ok()
+        .or(config.get_base_url().map(str::to_string));
+    match &base_url {
+        Some(url) if reqwest::Url::parse(url).is_ok() => println!("[OK] Base URL: {url}"),
+        Some(url) => fail(format!("Base URL is not a valid URL: {url}")),
+        None => fail("Base URL is not set".to_string()),
+    }
+
+    if env::var("API_KEY").is_ok() {
+        println!("[OK] API key is set by the environment");
+    } else {
+        match secret::resolve_api_key(config) {
+            Ok(Some(_)) => println!("[OK] API key is set"),
+            Ok(None) => fail("API key is not set".to_string()),
+            Err(e) => fail(e.to_string()),
+        }
+    }
+
+    // Malformed prices would be ignored, and the costs shown as unknown
+    for error in config.pricing_errors() {
+        fail(format!("Invalid price: {error}"));
+    }
+
+    let default_models = match env::var("DEFAULT_MODEL_ID") {
+        Ok(envvar) => split_list(&envvar),
+        Err(_) => config.get_default_models(),
+    };
+
+    // Try an actual request to the provider
+    let models_response = match Instance::new() {
+        Ok(api_instance) => api_instance.get_models().await,
+        Err(e) => Err(e),
+    };
+    match models_response {
+        Ok(response) => {
+            let model_collection = ModelCollection::new(&response);
+            println!("[OK] Connected to provider");
+            if default_models.is_empty() {
+                fail("Default model is not set".to_string());
+            }
+            for model_id in &default_models {
+                match model_collection.validate_model_id(model_id) {
+                    Ok(()) => println!("[OK] Default model: {model_id}"),
+                    Err(e) => fail(format!("Default model: {e}")),
+                }
+            }
+        }
+        Err(e) => fail(format!("Could not connect to provider: {e}")),
+    }
+
+    // Let scripts tell a broken setup apart
+    if failed {
+        return Err("Some checks failed".into());
+    }
+
+    Ok(())
+}
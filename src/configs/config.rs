@@ -1,12 +1,42 @@
+use clap::ValueEnum;
 use dotenv::dotenv;
 use std::fs;
-use std::path::Path;
-use toml_edit::{value, DocumentMut, Item, Table};
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, DocumentMut, Item, Table};
 
+use crate::clues::rules::ClueRules;
 use crate::configs::config_error::ConfigError;
+use crate::generation::GenerationParams;
+use crate::language::Language;
+use crate::pricing::Price;
+use crate::split_list;
+
+/// Every setting that can be read or changed with `mastermind config`
+pub const KEYS: [&str; 18] = [
+    "api.base-url",
+    "api.key",
+    "api.key-command",
+    "api.key-file",
+    "model.default",
+    "cache.ttl",
+    "clues.language",
+    "clues.min-clues",
+    "clues.max-clues",
+    "clues.min-linked",
+    "clues.end-game",
+    "generation.temperature",
+    "generation.top-p",
+    "generation.max-tokens",
+    "generation.seed",
+    "generation.presence-penalty",
+    "generation.frequency-penalty",
+    "generation.stop",
+];
 
 pub struct Config {
     document: DocumentMut,
+    path: PathBuf,
+    first_run: bool,
 }
 
 impl Config {
@@ -36,10 +66,20 @@ impl Config {
         // Define config file path
         let config_file = mastermind_dir.join("config.toml");
 
+        Self::load(config_file)
+    }
+
+    /// Read the config file at `path`, creating it with empty values if it doesn't exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let config_file = path.as_ref().to_path_buf();
+
         // Read or create a document
+        let mut first_run = false;
         let document = match fs::read_to_string(&config_file) {
             Ok(content) if !content.is_empty() => content.parse::<DocumentMut>()?,
             _ => {
+                first_run = true;
+
                 let mut doc = DocumentMut::new();
 
                 // Make .toml file in table-like format
@@ -51,20 +91,17 @@ impl Config {
                 doc["model"]["default"] = value("");
 
                 // Write the document to the config file
-                println!(
-                    "Looks like it's your first run\n\
-                          Creating a config file at {}\n\
-                          Make sure to modify it first or use the proper environment variables\n\
-                          See: https://github.com/theoforger/mastermind?tab=readme-ov-file#%EF%B8%8F-configure",
-                    config_file.display()
-                );
                 fs::write(&config_file, doc.to_string())?;
 
                 doc
             }
         };
 
-        Ok(Config { document })
+        Ok(Config {
+            document,
+            path: config_file,
+            first_run,
+        })
     }
 
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
@@ -72,23 +109,374 @@ impl Config {
         Ok(())
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the config file was just created by this instance
+    pub fn is_first_run(&self) -> bool {
+        self.first_run
+    }
+
     pub fn get_base_url(&self) -> Option<&str> {
-        self.document["api"]["base-url"]
-            .as_str()
-            .filter(|s| !s.is_empty())
+        self.get_str("api", "base-url")
     }
 
     pub fn get_api_key(&self) -> Option<&str> {
-        self.document["api"]["key"]
-            .as_str()
-            .filter(|s| !s.is_empty())
+        self.get_str("api", "key")
+    }
+
+    pub fn get_api_key_command(&self) -> Option<&str> {
+        self.get_str("api", "key-command")
+    }
+
+    pub fn get_api_key_file(&self) -> Option<&str> {
+        self.get_str("api", "key-file")
     }
 
-    pub fn get_default_model(&self) -> Option<&str> {
-        self.document["model"]["default"]
-            .as_str()
+    /// The default language models, written either as a single string or as a list
+    pub fn get_default_models(&self) -> Vec<String> {
+        match self.document.get("model").and_then(|t| t.get("default")) {
+            Some(Item::Value(v)) if v.is_array() => v
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|model_id| model_id.as_str())
+                .map(|model_id| model_id.trim().to_string())
+                .filter(|model_id| !model_id.is_empty())
+                .collect(),
+            _ => self
+                .get_str("model", "default")
+                .map(|model_id| vec![model_id.to_string()])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Store the default language models. A single model is kept as a plain string.
+    pub fn set_default_models(&mut self, model_ids: &[String]) -> Result<(), ConfigError> {
+        match model_ids {
+            [] => self.unset("model.default"),
+            [model_id] => self.set("model.default", model_id),
+            _ => {
+                if !self.document.contains_table("model") {
+                    self.document["model"] = Item::Table(Table::new());
+                }
+                let array: Array = model_ids.iter().collect();
+                self.document["model"]["default"] = value(array);
+                Ok(())
+            }
+        }
+    }
+
+    fn get_str(&self, table: &str, name: &str) -> Option<&str> {
+        self.document
+            .get(table)
+            .and_then(|t| t.get(name))
+            .and_then(Item::as_str)
             .filter(|s| !s.is_empty())
     }
+
+    /// How long cached responses stay fresh, in seconds
+    pub fn get_cache_ttl(&self) -> Option<u64> {
+        self.document
+            .get("cache")
+            .and_then(|t| t.get("ttl"))
+            .and_then(Item::as_integer)
+            .and_then(|ttl| u64::try_from(ttl).ok())
+    }
+
+    /// Language of the board and the clues, as a code like `de`
+    pub fn get_language(&self) -> Option<Language> {
+        self.get_str("clues", "language")
+            .and_then(|code| Language::from_str(code, true).ok())
+    }
+
+    /// How many clues to ask for and how many words each must link, with the defaults
+    /// for the settings that aren't set
+    pub fn get_clue_rules(&self) -> ClueRules {
+        let number = |name: &str| {
+            self.document
+                .get("clues")
+                .and_then(|t| t.get(name))
+                .and_then(Item::as_integer)
+                .and_then(|n| usize::try_from(n).ok())
+        };
+
+        let defaults = ClueRules::default();
+        ClueRules {
+            min_clues: number("min-clues").unwrap_or(defaults.min_clues),
+            max_clues: number("max-clues").unwrap_or(defaults.max_clues),
+            min_linked: number("min-linked").unwrap_or(defaults.min_linked),
+            end_game: number("end-game").unwrap_or(defaults.end_game),
+        }
+    }
+
+    /// Prices from the `[pricing."model-id"]` tables, in USD per million tokens. Malformed
+    /// entries are skipped, `pricing_errors` lists them.
+    pub fn get_prices(&self) -> Vec<(String, Price)> {
+        self.read_prices()
+            .into_iter()
+            .filter_map(|(model_id, price)| Some((model_id, price.ok()?)))
+            .collect()
+    }
+
+    /// One message per malformed `[pricing."model-id"]` table
+    pub fn pricing_errors(&self) -> Vec<String> {
+        self.read_prices()
+            .into_iter()
+            .filter_map(|(model_id, price)| {
+                Some(format!("pricing.\"{model_id}\": {}", price.err()?))
+            })
+            .collect()
+    }
+
+    fn read_prices(&self) -> Vec<(String, Result<Price, String>)> {
+        let Some(pricing) = self.document.get("pricing").and_then(Item::as_table_like) else {
+            return vec![];
+        };
+
+        let number = |table: &Item, name: &str| {
+            table
+                .get(name)
+                .and_then(|item| item.as_float().or(item.as_integer().map(|n| n as f64)))
+                .filter(|price| *price >= 0.0)
+                .ok_or(format!("expected a price of at least 0 for '{name}'"))
+        };
+
+        pricing
+            .iter()
+            .map(|(model_id, table)| {
+                let price = if table.is_table_like() {
+                    number(table, "prompt").and_then(|prompt| {
+                        let completion = number(table, "completion")?;
+                        Ok(Price { prompt, completion })
+                    })
+                } else {
+                    Err("expected a table with 'prompt' and 'completion'".to_string())
+                };
+                (model_id.to_string(), price)
+            })
+            .collect()
+    }
+
+    /// Generation settings from the `[generation]` table for every model, and from the
+    /// `[generation."model-id"]` tables for one model
+    pub fn get_generation_params(
+        &self,
+    ) -> Result<(GenerationParams, Vec<(String, GenerationParams)>), ConfigError> {
+        let Some(generation) = self
+            .document
+            .get("generation")
+            .and_then(Item::as_table_like)
+        else {
+            return Ok((GenerationParams::default(), vec![]));
+        };
+
+        let defaults = GenerationParams::from_table(generation, "generation")?;
+        let models = generation
+            .iter()
+            .filter_map(|(model_id, item)| Some((model_id, item.as_table_like()?)))
+            .map(|(model_id, table)| {
+                let params =
+                    GenerationParams::from_table(table, &format!("generation.\"{model_id}\""))?;
+                Ok((model_id.to_string(), params))
+            })
+            .collect::<Result<_, ConfigError>>()?;
+
+        Ok((defaults, models))
+    }
+
+    /// Read a setting by its dotted key, e.g. `api.base-url`
+    pub fn get(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        let (table, name) = Self::split_key(key)?;
+
+        let value = match self.document.get(table).and_then(|t| t.get(name)) {
+            Some(Item::Value(v)) => match (v.as_str(), v.as_array()) {
+                (Some(""), _) => None,
+                (Some(s), _) => Some(s.to_string()),
+                (None, Some(array)) => Some(
+                    array
+                        .iter()
+                        .map(|item| item.as_str().map_or(item.to_string(), str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                (None, None) => Some(v.to_string().trim().to_string()),
+            },
+            _ => None,
+        };
+
+        Ok(value)
+    }
+
+    /// Change a setting by its dotted key, keeping any comment attached to the old value.
+    /// `model.default` also accepts a comma-separated list, `generation.stop` is always one.
+    pub fn set(&mut self, key: &str, new_value: &str) -> Result<(), ConfigError> {
+        let (table, name) = Self::split_key(key)?;
+        Self::validate(key, new_value)?;
+
+        if key == "model.default" && new_value.contains(',') {
+            return self.set_default_models(&split_list(new_value));
+        }
+
+        if !self.document.contains_table(table) {
+            self.document[table] = Item::Table(Table::new());
+        }
+
+        let mut item = match key {
+            "cache.ttl"
+            | "clues.min-clues"
+            | "clues.max-clues"
+            | "clues.min-linked"
+            | "clues.end-game"
+            | "generation.max-tokens"
+            | "generation.seed" => value(new_value.parse::<i64>().expect("Validated above")),
+            "generation.temperature"
+            | "generation.top-p"
+            | "generation.presence-penalty"
+            | "generation.frequency-penalty" => {
+                value(new_value.parse::<f64>().expect("Validated above"))
+            }
+            "generation.stop" => value(split_list(new_value).iter().collect::<Array>()),
+            _ => value(new_value),
+        };
+        if let (Some(Item::Value(old)), Some(new)) =
+            (self.document[table].get(name), item.as_value_mut())
+        {
+            *new.decor_mut() = old.decor().clone();
+        }
+        self.document[table][name] = item;
+
+        Ok(())
+    }
+
+    /// Remove a setting by its dotted key
+    pub fn unset(&mut self, key: &str) -> Result<(), ConfigError> {
+        let (table, name) = Self::split_key(key)?;
+
+        if let Some(table) = self.document.get_mut(table).and_then(Item::as_table_mut) {
+            table.remove(name);
+        }
+
+        Ok(())
+    }
+
+    /// List every known setting with its current value. Secrets are masked.
+    pub fn list(&self) -> Vec<(&'static str, Option<String>)> {
+        KEYS.iter()
+            .map(|&key| {
+                let value = self.get(key).ok().flatten();
+                if key == "api.key" {
+                    (key, value.as_deref().map(mask_secret))
+                } else {
+                    (key, value)
+                }
+            })
+            .collect()
+    }
+
+    fn split_key(key: &str) -> Result<(&str, &str), ConfigError> {
+        if !KEYS.contains(&key) {
+            return Err(ConfigError::InvalidKey(format!(
+                "'{key}' (valid keys: {})",
+                KEYS.join(", ")
+            )));
+        }
+
+        Ok(key
+            .split_once('.')
+            .expect("Every known key should contain a table name"))
+    }
+
+    pub(crate) fn validate(key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "api.base-url" => {
+                let url = reqwest::Url::parse(value)
+                    .map_err(|e| ConfigError::InvalidValue(format!("{key}: {e}")))?;
+                if !matches!(url.scheme(), "http" | "https") {
+                    return Err(ConfigError::InvalidValue(format!(
+                        "{key}: URL must start with http:// or https://"
+                    )));
+                }
+            }
+            "cache.ttl" if value.parse::<u32>().is_err() => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{key}: expected a number of seconds"
+                )));
+            }
+            "clues.min-clues" | "clues.max-clues" | "clues.min-linked" | "clues.end-game"
+                if value.parse::<u32>().is_err() =>
+            {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{key}: expected a whole number"
+                )));
+            }
+            "clues.language" if Language::from_str(value, true).is_err() => {
+                let codes: Vec<String> = Language::value_variants()
+                    .iter()
+                    .filter_map(|language| language.to_possible_value())
+                    .map(|value| value.get_name().to_string())
+                    .collect();
+                return Err(ConfigError::InvalidValue(format!(
+                    "{key}: expected one of {}",
+                    codes.join(", ")
+                )));
+            }
+            "generation.stop" => {
+                let mut table = Table::new();
+                table.insert(
+                    "stop",
+                    toml_edit::value(split_list(value).iter().collect::<Array>()),
+                );
+                GenerationParams::from_table(&table, "generation")?;
+            }
+            _ if key.starts_with("generation.") => {
+                let mut table = Table::new();
+                let name = key.trim_start_matches("generation.");
+                let number = match (name, value.parse::<i64>(), value.parse::<f64>()) {
+                    ("max-tokens" | "seed", Ok(number), _) => toml_edit::value(number),
+                    ("max-tokens" | "seed", Err(_), _) => {
+                        return Err(ConfigError::InvalidValue(format!(
+                            "{key}: expected a whole number"
+                        )))
+                    }
+                    (_, _, Ok(number)) => toml_edit::value(number),
+                    (_, _, Err(_)) => {
+                        return Err(ConfigError::InvalidValue(format!(
+                            "{key}: expected a number"
+                        )))
+                    }
+                };
+                table.insert(name, number);
+                GenerationParams::from_table(&table, "generation")?;
+            }
+            "api.key-file" if !Path::new(value).is_file() => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{key}: '{value}' is not a file"
+                )));
+            }
+            "api.key" | "api.key-command" | "model.default" if value.trim().is_empty() => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{key}: value cannot be empty, use `unset` instead"
+                )));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Hide most of a secret, keeping just enough to tell keys apart
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 12 {
+        return "*".repeat(8);
+    }
+
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}...{tail}")
 }
 
 #[cfg(test)]
@@ -109,6 +497,7 @@ mod tests {
         // Create a config
         let config_result = Config::new();
         assert!(config_result.is_ok());
+        assert!(config_result.unwrap().is_first_run());
         assert!(config_dir.exists());
 
         // Check if config.toml exists
@@ -123,4 +512,161 @@ mod tests {
         assert!(content.contains("[model]"));
         assert!(content.contains("default"));
     }
+
+    #[test]
+    fn test_get_set_unset() {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_file,
+            "[api]\nbase-url = \"\" # provider URL\nkey = \"\"\n\n[model]\ndefault = \"\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::load(&config_file).unwrap();
+        assert!(!config.is_first_run());
+        assert_eq!(config.get("api.base-url").unwrap(), None);
+
+        // Set and save, the comment should survive
+        config
+            .set("api.base-url", "https://api.example.com/v1/")
+            .unwrap();
+        config.save(config.path()).unwrap();
+        let content = fs::read_to_string(&config_file).unwrap();
+        assert!(content.contains("base-url = \"https://api.example.com/v1/\" # provider URL"));
+
+        let config = Config::load(&config_file).unwrap();
+        assert_eq!(config.get_base_url(), Some("https://api.example.com/v1/"));
+
+        // Unset
+        let mut config = config;
+        config.unset("api.base-url").unwrap();
+        assert_eq!(config.get("api.base-url").unwrap(), None);
+
+        // Numbers are stored as numbers
+        config.set("cache.ttl", "3600").unwrap();
+        assert_eq!(config.get_cache_ttl(), Some(3600));
+
+        config.set("clues.language", "fr").unwrap();
+        assert_eq!(config.get_language(), Some(Language::French));
+
+        // Clue rules
+        config.set("clues.min-linked", "3").unwrap();
+        assert!(config.set("clues.end-game", "-1").is_err());
+        assert_eq!(config.get_clue_rules().min_linked, 3);
+        assert_eq!(config.get_clue_rules().max_clues, 10);
+
+        // Generation settings are checked against their ranges
+        config.set("generation.temperature", "0.2").unwrap();
+        config.set("generation.seed", "42").unwrap();
+        assert!(config.set("generation.top-p", "2").is_err());
+        assert!(config.set("generation.max-tokens", "lots").is_err());
+        let (defaults, models) = config.get_generation_params().unwrap();
+        assert_eq!(defaults.temperature, Some(0.2));
+        assert_eq!(defaults.seed, Some(42));
+        assert!(models.is_empty());
+
+        // Stop sequences are a list of strings
+        config.set("generation.stop", "###, END").unwrap();
+        assert_eq!(
+            config.get("generation.stop").unwrap().as_deref(),
+            Some("###, END")
+        );
+        assert_eq!(
+            config.get_generation_params().unwrap().0.stop,
+            ["###", "END"]
+        );
+        assert!(config.set("generation.stop", "a, b, c, d, e").is_err());
+
+        // Unknown keys
+        assert!(config.get("api.nope").is_err());
+        assert!(config.set("nope", "value").is_err());
+    }
+
+    #[test]
+    fn test_default_models() {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(&config_file, "[model]\ndefault = \"model-a\"\n").unwrap();
+
+        let mut config = Config::load(&config_file).unwrap();
+        assert_eq!(config.get_default_models(), vec!["model-a"]);
+
+        // A list of models
+        let model_ids = vec!["model-a".to_string(), "model-b".to_string()];
+        config.set_default_models(&model_ids).unwrap();
+        config.save(config.path()).unwrap();
+        let content = fs::read_to_string(&config_file).unwrap();
+        assert!(content.contains("default = [\"model-a\", \"model-b\"]"));
+
+        let mut config = Config::load(&config_file).unwrap();
+        assert_eq!(config.get_default_models(), model_ids);
+        assert_eq!(
+            config.get("model.default").unwrap().as_deref(),
+            Some("model-a, model-b")
+        );
+
+        // Comma-separated values from `config set`
+        config.set("model.default", "model-c, model-d").unwrap();
+        assert_eq!(config.get_default_models(), vec!["model-c", "model-d"]);
+
+        // Back to a single model
+        config.set_default_models(&model_ids[..1]).unwrap();
+        assert_eq!(config.get_str("model", "default"), Some("model-a"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::validate("api.base-url", "https://api.groq.com/openai/v1/").is_ok());
+        assert!(Config::validate("api.base-url", "not a url").is_err());
+        assert!(Config::validate("api.base-url", "ftp://example.com").is_err());
+        assert!(Config::validate("api.key", "  ").is_err());
+        assert!(Config::validate("cache.ttl", "3600").is_ok());
+        assert!(Config::validate("cache.ttl", "-1").is_err());
+        assert!(Config::validate("model.default", "llama-3.1-70b-versatile").is_ok());
+        assert!(Config::validate("clues.language", "de").is_ok());
+        assert!(Config::validate("clues.language", "klingon").is_err());
+    }
+
+    #[test]
+    fn test_pricing_errors() {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_file,
+            "[pricing]\nflat = 3\n\n[pricing.\"gpt-4o\"]\nprompt = 1\ncompletion = 2.0\n\n\
+            [pricing.\"typo\"]\nprompt = 1\ncompletoin = 2\n\n\
+            [pricing.\"negative\"]\nprompt = -1\ncompletion = \"free\"\n",
+        )
+        .unwrap();
+
+        // Malformed entries are skipped, and reported
+        let config = Config::load(&config_file).unwrap();
+        let prices = config.get_prices();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].0, "gpt-4o");
+
+        let errors = config.pricing_errors();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("pricing.\"flat\""));
+        assert!(errors[1].contains("'completion'"));
+        assert!(errors[2].contains("'prompt'"));
+    }
+
+    #[test]
+    fn test_list_masks_key() {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_file,
+            "[api]\nbase-url = \"\"\nkey = \"sk-1234567890abcdef\"\n\n[model]\ndefault = \"\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load(&config_file).unwrap();
+        let list = config.list();
+        let (_, key) = list.iter().find(|(k, _)| *k == "api.key").unwrap();
+        assert_eq!(key.as_deref(), Some("sk-...cdef"));
+        assert_eq!(mask_secret("short"), "********");
+    }
 }
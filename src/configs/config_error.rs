@@ -6,6 +6,8 @@ pub enum ConfigError {
     FileNotFound(String),
     ParseError(String),
     IoError(String),
+    InvalidKey(String),
+    InvalidValue(String),
 }
 
 impl fmt::Display for ConfigError {
@@ -14,6 +16,8 @@ impl fmt::Display for ConfigError {
             ConfigError::FileNotFound(msg) => write!(f, "File not found: {msg}"),
             ConfigError::ParseError(msg) => write!(f, "Parse error: {msg}"),
             ConfigError::IoError(err) => write!(f, "IO error: {err}"),
+            ConfigError::InvalidKey(msg) => write!(f, "Unknown setting: {msg}"),
+            ConfigError::InvalidValue(msg) => write!(f, "Invalid value for {msg}"),
         }
     }
 }
@@ -1,2 +1,4 @@
+pub mod command;
 pub mod config;
 pub(crate) mod config_error;
+pub mod setup;
@@ -0,0 +1,165 @@
+use dialoguer::{Confirm, Input, Password, Select};
+use std::env;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+
+use crate::api::secret::{self, Secret};
+use crate::api::Instance;
+use crate::configs::config::Config;
+use crate::model_collection::ModelCollection;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1/";
+
+/// Greet a new user with the setup wizard.
+/// Falls back to printing instructions when stdin is not a terminal.
+pub async fn first_run(config: &mut Config) -> Result<(), Box<dyn Error>> {
+    // Nothing to ask if the environment already provides the credentials
+    if env::var("OPENAI_API_BASE_URL").is_ok() && env::var("API_KEY").is_ok() {
+        return Ok(());
+    }
+
+    if !io::stdin().is_terminal() {
+        println!(
+            "Looks like it's your first run\n\
+            Created a config file at {}\n\
+            Make sure to modify it first or use the proper environment variables\n\
+            See: https://github.com/theoforger/mastermind?tab=readme-ov-file#%EF%B8%8F-configure",
+            config.path().display()
+        );
+        return Ok(());
+    }
+
+    println!("Looks like it's your first run. Let's set things up!");
+    wizard(config).await
+}
+
+/// Ask for the provider's base URL, API key and a default model, then save them
+pub async fn wizard(config: &mut Config) -> Result<(), Box<dyn Error>> {
+    if !io::stdin().is_terminal() {
+        return Err("The setup wizard needs an interactive terminal".into());
+    }
+
+    println!(
+        "Your answers will be saved to {}\n",
+        config.path().display()
+    );
+
+    loop {
+        let base_url: String = Input::new()
+            .with_prompt("Base URL of an OpenAI-compatible API")
+            .with_initial_text(config.get_base_url().unwrap_or(DEFAULT_BASE_URL))
+            .validate_with(|input: &String| {
+                Config::validate("api.base-url", input).map_err(|e| e.to_string())
+            })
+            .interact_text()?;
+
+        let key_source = match ask_key_source() {
+            Ok(key_source) => key_source,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+
+        // Make sure the provider accepts these credentials before saving them
+        println!("Checking connection...");
+        let api_instance = Instance::builder(&base_url)
+            .api_key(key_source.secret().expose())
+            .build();
+        let models_response = match api_instance.get_models().await {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("{e}");
+                if Confirm::new()
+                    .with_prompt("Could not reach your provider. Try again?")
+                    .default(true)
+                    .interact()?
+                {
+                    continue;
+                }
+                return Err("Setup cancelled. Run `mastermind config init` to try again".into());
+            }
+        };
+
+        config.set("api.base-url", &base_url)?;
+        for key in ["api.key", "api.key-command", "api.key-file"] {
+            config.unset(key)?;
+        }
+        match &key_source {
+            KeySource::Command(command, _) => config.set("api.key-command", command)?,
+            KeySource::File(path, _) => config.set("api.key-file", path)?,
+            KeySource::Plaintext(key) => config.set("api.key", key.expose())?,
+            KeySource::None => {}
+        }
+
+        // Pick a default model
+        println!("Choose a default language model");
+        let model_collection = ModelCollection::new(&models_response);
+        let model_ids = model_collection.prompt_selection()?;
+        config.set_default_models(&model_ids)?;
+
+        config.save(config.path())?;
+        println!("All set! You can change these later with `mastermind config`\n");
+        return Ok(());
+    }
+}
+
+/// Where the API key comes from, along with the key itself for the connection check
+enum KeySource {
+    Command(String, Secret),
+    File(String, Secret),
+    Plaintext(Secret),
+    None,
+}
+
+impl KeySource {
+    fn secret(&self) -> Secret {
+        match self {
+            KeySource::Command(_, key) | KeySource::File(_, key) | KeySource::Plaintext(key) => {
+                key.clone()
+            }
+            KeySource::None => Secret::default(),
+        }
+    }
+}
+
+/// Prefer a password manager or a secret file, so that the key stays out of the config file
+fn ask_key_source() -> Result<KeySource, Box<dyn Error>> {
+    let choice = Select::new()
+        .with_prompt("How should mastermind get your API key?")
+        .items(&[
+            "Run a command that prints it, e.g. a password manager (recommended)",
+            "Read it from a file",
+            "Save it in the config file as plain text",
+            "My provider doesn't need one",
+        ])
+        .default(0)
+        .interact()?;
+
+    let key_source = match choice {
+        0 => {
+            let command: String = Input::new()
+                .with_prompt("Command, e.g. `pass show openai`")
+                .interact_text()?;
+            let key = secret::run_key_command(&command)?;
+            KeySource::Command(command, key)
+        }
+        1 => {
+            let path: String = Input::new()
+                .with_prompt("Path of the file")
+                .validate_with(|input: &String| {
+                    Config::validate("api.key-file", input).map_err(|e| e.to_string())
+                })
+                .interact_text()?;
+            let key = secret::read_key_file(&path)?;
+            KeySource::File(path, key)
+        }
+        2 => {
+            let key = Password::new().with_prompt("API key").interact()?;
+            KeySource::Plaintext(Secret::new(key))
+        }
+        _ => KeySource::None,
+    };
+
+    Ok(key_source)
+}
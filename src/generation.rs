@@ -0,0 +1,268 @@
+use serde_json::json;
+use toml_edit::{Item, TableLike};
+
+use crate::configs::config::Config;
+use crate::configs::config_error::ConfigError;
+
+/// Sampling settings sent along with each request. Unset values are left out, so the
+/// provider's defaults apply.
+#[derive(clap::Args, Clone, Default, PartialEq, Debug)]
+pub struct GenerationParams {
+    /// Sampling temperature, from 0 to 2. Lower is more focused
+    #[arg(long)]
+    pub temperature: Option<f64>,
+
+    /// Nucleus sampling, from 0 to 1
+    #[arg(long = "top-p")]
+    pub top_p: Option<f64>,
+
+    /// Maximum number of tokens in each answer
+    #[arg(long = "max-tokens")]
+    pub max_tokens: Option<u32>,
+
+    /// Seed for reproducible answers, on providers that support it
+    #[arg(long)]
+    pub seed: Option<i64>,
+
+    /// Penalty for words already used, from -2 to 2
+    #[arg(long = "presence-penalty", allow_negative_numbers = true)]
+    pub presence_penalty: Option<f64>,
+
+    /// Penalty for words used often, from -2 to 2
+    #[arg(long = "frequency-penalty", allow_negative_numbers = true)]
+    pub frequency_penalty: Option<f64>,
+
+    /// Stop generating at this text. Can be given up to 4 times
+    #[arg(long, value_name = "TEXT")]
+    pub stop: Vec<String>,
+}
+
+impl GenerationParams {
+    /// Read the settings of a `[generation]` table, named `name` in error messages
+    pub fn from_table(table: &dyn TableLike, name: &str) -> Result<Self, ConfigError> {
+        let invalid = |key: &str, expected: &str| {
+            ConfigError::InvalidValue(format!("{name}.{key}: expected {expected}"))
+        };
+        let number = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(item) => item
+                .as_float()
+                .or(item.as_integer().map(|n| n as f64))
+                .map(Some)
+                .ok_or_else(|| invalid(key, "a number")),
+        };
+        let integer = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(item) => item
+                .as_integer()
+                .map(Some)
+                .ok_or_else(|| invalid(key, "a whole number")),
+        };
+
+        let stop = match table.get("stop") {
+            None => vec![],
+            Some(Item::Value(value)) if value.is_str() => {
+                vec![value.as_str().unwrap_or_default().to_string()]
+            }
+            Some(item) => item
+                .as_array()
+                .and_then(|array| {
+                    array
+                        .iter()
+                        .map(|text| text.as_str().map(str::to_string))
+                        .collect()
+                })
+                .ok_or_else(|| invalid("stop", "a string or a list of strings"))?,
+        };
+
+        let params = Self {
+            temperature: number("temperature")?,
+            top_p: number("top-p")?,
+            max_tokens: integer("max-tokens")?
+                .map(|max_tokens| {
+                    u32::try_from(max_tokens)
+                        .map_err(|_| invalid("max-tokens", "a positive number"))
+                })
+                .transpose()?,
+            seed: integer("seed")?,
+            presence_penalty: number("presence-penalty")?,
+            frequency_penalty: number("frequency-penalty")?,
+            stop,
+        };
+        params
+            .validate()
+            .map_err(|e| ConfigError::InvalidValue(format!("{name}.{e}")))?;
+
+        Ok(params)
+    }
+
+    /// Check that every value is in the range accepted by OpenAI-compatible APIs
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |key: &str, value: Option<f64>, min: f64, max: f64| match value {
+            Some(value) if !(min..=max).contains(&value) => Err(format!(
+                "{key}: expected a number from {min} to {max}, got {value}"
+            )),
+            _ => Ok(()),
+        };
+        check("temperature", self.temperature, 0.0, 2.0)?;
+        check("top-p", self.top_p, 0.0, 1.0)?;
+        check("presence-penalty", self.presence_penalty, -2.0, 2.0)?;
+        check("frequency-penalty", self.frequency_penalty, -2.0, 2.0)?;
+
+        if self.max_tokens == Some(0) {
+            return Err("max-tokens: expected at least 1".to_string());
+        }
+        if self.stop.len() > 4 {
+            return Err("stop: expected at most 4 sequences".to_string());
+        }
+        if self.stop.iter().any(String::is_empty) {
+            return Err("stop: sequences cannot be empty".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Combine two sets of settings. The values set in `other` take priority.
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            temperature: other.temperature.or(self.temperature),
+            top_p: other.top_p.or(self.top_p),
+            max_tokens: other.max_tokens.or(self.max_tokens),
+            seed: other.seed.or(self.seed),
+            presence_penalty: other.presence_penalty.or(self.presence_penalty),
+            frequency_penalty: other.frequency_penalty.or(self.frequency_penalty),
+            stop: if other.stop.is_empty() {
+                self.stop.clone()
+            } else {
+                other.stop.clone()
+            },
+        }
+    }
+
+    /// Add the values that are set to a chat completions request body
+    pub fn apply(&self, request_body: &mut serde_json::Value) {
+        let fields = [
+            ("temperature", self.temperature.map(|value| json!(value))),
+            ("top_p", self.top_p.map(|value| json!(value))),
+            ("max_tokens", self.max_tokens.map(|value| json!(value))),
+            ("seed", self.seed.map(|value| json!(value))),
+            (
+                "presence_penalty",
+                self.presence_penalty.map(|value| json!(value)),
+            ),
+            (
+                "frequency_penalty",
+                self.frequency_penalty.map(|value| json!(value)),
+            ),
+            ("stop", (!self.stop.is_empty()).then(|| json!(self.stop))),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                request_body[key] = value;
+            }
+        }
+    }
+}
+
+/// Generation settings for every model: command line flags, then `[generation."model-id"]`,
+/// then `[generation]` in the config file
+#[derive(Clone, Default)]
+pub struct GenerationTable {
+    defaults: GenerationParams,
+    models: Vec<(String, GenerationParams)>,
+    overrides: GenerationParams,
+}
+
+impl GenerationTable {
+    pub fn new(
+        config: &Config,
+        overrides: GenerationParams,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        overrides.validate()?;
+        let (defaults, models) = config.get_generation_params()?;
+
+        Ok(Self {
+            defaults,
+            models,
+            overrides,
+        })
+    }
+
+    /// The settings for one model
+    pub fn get(&self, model_id: &str) -> GenerationParams {
+        let params = match self.models.iter().find(|(id, _)| id == model_id) {
+            Some((_, params)) => self.defaults.merge(params),
+            None => self.defaults.clone(),
+        };
+        params.merge(&self.overrides)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn load_config(content: &str) -> Config {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(&config_file, content).unwrap();
+        Config::load(&config_file).unwrap()
+    }
+
+    #[test]
+    fn test_generation_table() {
+        let config = load_config(
+            "[generation]\ntemperature = 0.7\nmax-tokens = 500\n\n\
+            [generation.\"gpt-4o\"]\ntemperature = 0\nseed = 42\nstop = \"###\"\n",
+        );
+        let overrides = GenerationParams {
+            max_tokens: Some(300),
+            ..Default::default()
+        };
+        let table = GenerationTable::new(&config, overrides).unwrap();
+
+        let params = table.get("gpt-4o");
+        assert_eq!(params.temperature, Some(0.0));
+        assert_eq!(params.max_tokens, Some(300));
+        assert_eq!(params.seed, Some(42));
+        assert_eq!(params.stop, ["###"]);
+
+        let params = table.get("local-model");
+        assert_eq!(params.temperature, Some(0.7));
+        assert_eq!(params.seed, None);
+
+        // Only the values that are set end up in the request
+        let mut request_body = json!({ "model": "local-model" });
+        params.apply(&mut request_body);
+        assert_eq!(
+            request_body,
+            json!({ "model": "local-model", "temperature": 0.7, "max_tokens": 300 })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(GenerationParams::default().validate().is_ok());
+
+        let params = GenerationParams {
+            temperature: Some(2.5),
+            ..Default::default()
+        };
+        assert!(params.validate().unwrap_err().starts_with("temperature"));
+
+        let params = GenerationParams {
+            presence_penalty: Some(-2.0),
+            stop: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into()],
+            ..Default::default()
+        };
+        assert!(params.validate().unwrap_err().starts_with("stop"));
+
+        // Config values are checked too
+        let config = load_config("[generation.\"gpt-4o\"]\ntop-p = 1.5\n");
+        assert!(GenerationTable::new(&config, GenerationParams::default()).is_err());
+        let config = load_config("[generation]\nseed = \"random\"\n");
+        assert!(GenerationTable::new(&config, GenerationParams::default()).is_err());
+    }
+}
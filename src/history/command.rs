@@ -0,0 +1,381 @@
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Table};
+use dialoguer::{Confirm, Input, Select};
+use std::error::Error;
+use std::io::{self, IsTerminal};
+
+use super::{format_timestamp, History, Session};
+use crate::clues::clue::Clue;
+use crate::clues::clue_collection::ClueCollection;
+use crate::{append_used_clue, write_content_to_file, ExportFormat, HistoryCommand};
+
+/// Carry out a `mastermind history` subcommand
+pub fn run(command: &HistoryCommand) -> Result<(), Box<dyn Error>> {
+    let history = History::new()?;
+
+    match command {
+        HistoryCommand::List { limit } => {
+            let sessions = history.load()?;
+            if sessions.is_empty() {
+                println!("No games recorded yet");
+            } else {
+                println!("{}", list_table(sessions.iter().rev().take(*limit)));
+            }
+        }
+        HistoryCommand::Show { id } => print!("{}", show(&history.get(*id)?)),
+        HistoryCommand::Review { id } => {
+            let mut session = match id {
+                Some(id) => history.get(*id)?,
+                None => history
+                    .load()?
+                    .pop()
+                    .ok_or("No games recorded yet. Generate some clues first")?,
+            };
+            review(&mut session)?;
+            history.update(&session)?;
+            println!("Saved game #{}", session.id);
+
+            // The clue can't be given again in this game
+            if let (Some(game), Some(given)) = (&session.game, &session.given) {
+                append_used_clue(game, given.clue_word())?;
+                println!("Added '{}' to {}", given.clue_word(), game.display());
+            }
+        }
+        HistoryCommand::Export { format, output } => {
+            let content = export(&history.load()?, *format)?;
+            match output {
+                Some(path) => {
+                    println!("Writing to file '{}'...", path.display());
+                    write_content_to_file(path, content)?;
+                }
+                None => print!("{content}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list_table<'a>(sessions: impl Iterator<Item = &'a Session>) -> Table {
+    let mut table = Table::new();
+
+    // Set up header and styles
+    table
+        .set_header(
+            [
+                "Game", "Date", "Models", "Clues", "Given", "Guesses", "Outcome",
+            ]
+            .map(|header| {
+                Cell::new(header)
+                    .add_attribute(Attribute::Bold)
+                    .set_alignment(CellAlignment::Center)
+            }),
+        )
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS);
+
+    for session in sessions {
+        table.add_row(vec![
+            format!("#{}", session.id),
+            format_timestamp(session.timestamp),
+            session.models().join(", "),
+            session.clues.len().to_string(),
+            session.given.as_ref().map_or("-".to_string(), |clue| {
+                format!("{} {}", clue.clue_word(), clue.count())
+            }),
+            session.guesses.join(", "),
+            session
+                .outcome
+                .map_or("-".to_string(), |outcome| outcome.to_string()),
+        ]);
+    }
+
+    table
+}
+
+/// The board, every clue and what happened after the clue was given
+fn show(session: &Session) -> String {
+    let mut text = format!(
+        "Game #{} ({} UTC)\n\nTo Link: {}\nTo Avoid: {}\n",
+        session.id,
+        format_timestamp(session.timestamp),
+        session.to_link.join(", "),
+        session.to_avoid.join(", ")
+    );
+    if !session.assassins.is_empty() {
+        text.push_str(&format!("Assassins: {}\n", session.assassins.join(", ")));
+    }
+
+    text.push_str(&format!(
+        "\n{}\n",
+        ClueCollection::from_clues(session.clues.clone())
+    ));
+
+    match &session.given {
+        Some(clue) => {
+            text.push_str(&format!(
+                "\nGiven: {} {} ({})\n",
+                clue.clue_word(),
+                clue.count(),
+                clue.source()
+            ));
+
+            // Mark each guess as one of your agents or not
+            let guesses: Vec<String> = session
+                .guesses
+                .iter()
+                .map(|guess| {
+                    let mark = if session.to_link.contains(guess) {
+                        '✓'
+                    } else {
+                        '✗'
+                    };
+                    format!("{guess} {mark}")
+                })
+                .collect();
+            text.push_str(&format!("Guesses: {}\n", guesses.join(", ")));
+            if let Some(outcome) = session.outcome {
+                text.push_str(&format!("Outcome: {outcome}\n"));
+            }
+        }
+        None => text.push_str(&format!(
+            "\nNo clue recorded. Run `mastermind history review {}` after your turn\n",
+            session.id
+        )),
+    }
+
+    text
+}
+
+/// Ask which clue was given and what the team guessed
+fn review(session: &mut Session) -> Result<(), Box<dyn Error>> {
+    if !io::stdin().is_terminal() {
+        return Err("Reviewing a game needs an interactive terminal".into());
+    }
+
+    println!(
+        "Game #{} ({} UTC)",
+        session.id,
+        format_timestamp(session.timestamp)
+    );
+
+    // Pick one of the suggestions, or type the clue if it came from somewhere else
+    let mut items: Vec<String> = session
+        .clues
+        .iter()
+        .map(|clue| {
+            format!(
+                "{} {} - {} ({})",
+                clue.clue_word(),
+                clue.count(),
+                clue.linked_words().join(", "),
+                clue.source()
+            )
+        })
+        .collect();
+    items.push("Another clue".to_string());
+    let choice = Select::new()
+        .with_prompt("Which clue did you give?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    let given = match session.clues.get(choice) {
+        Some(clue) => clue.clone(),
+        None => {
+            let clue_word: String = Input::new().with_prompt("Clue").interact_text()?;
+            let count: usize = Input::new().with_prompt("Count").interact_text()?;
+            Clue::given(&clue_word, count, session.language)
+        }
+    };
+
+    // Guesses are checked against the board so that typos don't count as wrong guesses
+    let board_words: Vec<String> = session
+        .to_link
+        .iter()
+        .chain(&session.to_avoid)
+        .cloned()
+        .collect();
+    let guesses: String = Input::new()
+        .with_prompt("Words guessed by your team, in order and separated by commas")
+        .allow_empty(true)
+        .validate_with(|input: &String| {
+            match split_guesses(input, session)
+                .into_iter()
+                .find(|guess| !board_words.contains(guess))
+            {
+                Some(guess) => Err(format!("'{guess}' is not on the board")),
+                None => Ok(()),
+            }
+        })
+        .interact_text()?;
+
+    // Standard boards don't say which word is the assassin, so ask about the wrong guess
+    session.given = Some(given);
+    let guesses = split_guesses(&guesses, session);
+    session.record_guesses(guesses, |guess| {
+        Confirm::new()
+            .with_prompt(format!("Was '{guess}' the assassin?"))
+            .default(false)
+            .interact()
+            .unwrap_or(false)
+    });
+
+    Ok(())
+}
+
+/// Guesses are spelled like the cards of the board: words following the rules of its
+/// language, or uppercase coordinates for pictures
+fn split_guesses(input: &str, session: &Session) -> Vec<String> {
+    input
+        .split(',')
+        .map(|guess| {
+            if session.is_pictures() {
+                return guess.trim().to_uppercase();
+            }
+            // Spelled like the board, whatever the case typed
+            let guess = session.language.normalize(guess);
+            session
+                .to_link
+                .iter()
+                .chain(&session.to_avoid)
+                .find(|word| session.language.same_word(word, &guess))
+                .cloned()
+                .unwrap_or(guess)
+        })
+        .filter(|guess| !guess.is_empty())
+        .collect()
+}
+
+/// Every session as JSON lines, or one CSV row per clue for spreadsheets
+fn export(sessions: &[Session], format: ExportFormat) -> Result<String, Box<dyn Error>> {
+    let mut content = String::new();
+
+    match format {
+        ExportFormat::Jsonl => {
+            for session in sessions {
+                content.push_str(&serde_json::to_string(session)?);
+                content.push('\n');
+            }
+        }
+        ExportFormat::Csv => {
+            content.push_str(
+                "game,date,model,clue,count,linked_words,given,correct_guesses,outcome\n",
+            );
+            for session in sessions {
+                for clue in &session.clues {
+                    let given = session.given.as_ref().is_some_and(|given| {
+                        given.clue_word() == clue.clue_word() && given.source() == clue.source()
+                    });
+                    let fields = [
+                        session.id.to_string(),
+                        format_timestamp(session.timestamp),
+                        clue.source().to_string(),
+                        clue.clue_word().to_string(),
+                        clue.count().to_string(),
+                        clue.linked_words().join(", "),
+                        given.to_string(),
+                        if given {
+                            session.correct_guesses().to_string()
+                        } else {
+                            String::new()
+                        },
+                        match (given, session.outcome) {
+                            (true, Some(outcome)) => outcome.to_string(),
+                            _ => String::new(),
+                        },
+                    ];
+                    let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                    content.push_str(&row.join(","));
+                    content.push('\n');
+                }
+            }
+        }
+    }
+
+    Ok(content)
+}
+
+/// Quote a CSV field when it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::language::Language;
+
+    fn session() -> Session {
+        let response = serde_json::from_value(serde_json::json!({
+            "model": "model-a",
+            "choices": [{ "message": { "content": "music, 2, sound, bee\nfilm, 2, bond, tokyo" } }],
+            "usage": { "prompt_tokens": 100, "completion_tokens": 20, "total_tokens": 120 }
+        }))
+        .unwrap();
+        let board = Board::new(
+            vec!["sound".into(), "bee".into(), "bond".into(), "tokyo".into()],
+            vec!["park".into()],
+        );
+
+        let mut session = Session::new(7, &board, &ClueCollection::new(vec![response]), None);
+        session.given = Some(session.clues[0].clone());
+        session.record_guesses(vec!["sound".into(), "park".into()], |_| false);
+        session
+    }
+
+    #[test]
+    fn test_show() {
+        let text = show(&session());
+        assert!(text.starts_with("Game #7"));
+        assert!(text.contains("To Avoid: park"));
+        assert!(text.contains("film"));
+        assert!(text.contains("Given: music 2 (model-a)"));
+        assert!(text.contains("Guesses: sound ✓, park ✗"));
+        assert!(text.contains("Outcome: hit a word to avoid"));
+
+        let table = list_table([session()].iter()).to_string();
+        assert!(table.contains("#7"));
+        assert!(table.contains("music 2"));
+    }
+
+    #[test]
+    fn test_export() {
+        let sessions = [session()];
+
+        let jsonl = export(&sessions, ExportFormat::Jsonl).unwrap();
+        let parsed: Session = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(parsed.id, 7);
+
+        let csv = export(&sessions, ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("7,"));
+        assert!(lines[1].ends_with(",model-a,music,2,\"sound, bee\",true,1,hit a word to avoid"));
+        assert!(lines[2].ends_with(",false,,"));
+    }
+
+    #[test]
+    fn test_split_guesses() {
+        let mut session = session();
+        assert_eq!(split_guesses(" Sound, bee ,,", &session), ["sound", "bee"]);
+        assert!(split_guesses("", &session).is_empty());
+        // Words on the board are matched case folded, the others kept as typed
+        session.language = Language::Turkish;
+        session.to_link.push("ırmak".into());
+        assert_eq!(split_guesses("IRMAK, Deniz", &session), ["ırmak", "Deniz"]);
+
+        // Pictures are guessed by coordinate
+        let board = Board::new(vec!["A1".into(), "B3".into()], vec!["C2".into()]);
+        let session = Session::new(8, &board, &ClueCollection::new(vec![]), None);
+        assert!(session.is_pictures());
+        assert_eq!(split_guesses("a1, b3", &session), ["A1", "B3"]);
+    }
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::pictures::is_coordinate;
+use crate::board::Board;
+use crate::clues::clue::Clue;
+use crate::clues::clue_collection::ClueCollection;
+use crate::json::chat_completions::Usage;
+use crate::language::Language;
+use crate::model_collection::format_date;
+
+pub mod command;
+pub mod stats;
+
+/// How the turn ended after the clue was given
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// Every guess was one of your agents
+    Correct,
+    /// The last guess was an opponent's agent or a neutral card
+    Avoid,
+    /// The last guess was the assassin
+    Assassin,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Outcome::Correct => "correct",
+            Outcome::Avoid => "hit a word to avoid",
+            Outcome::Assassin => "hit the assassin",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// Token usage of one model during a session
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModelTokens {
+    pub model: String,
+    pub usage: Usage,
+    /// Whether the answer came from the cache, so the tokens weren't paid for again
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// One run of clue generation, and what happened at the table afterwards
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub id: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub to_link: Vec<String>,
+    pub to_avoid: Vec<String>,
+    #[serde(default)]
+    pub assassins: Vec<String>,
+    /// Every clue suggested, by every model
+    pub clues: Vec<Clue>,
+    #[serde(default)]
+    pub usage: Vec<ModelTokens>,
+    /// The clue actually given, entered with `history review`
+    #[serde(default)]
+    pub given: Option<Clue>,
+    /// The words guessed by your team, in order
+    #[serde(default)]
+    pub guesses: Vec<String>,
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+    /// Language of the board, which guesses are normalized with
+    #[serde(default)]
+    pub language: Language,
+    /// The `--game` file listing the clues given so far, which the reviewed clue is added to
+    #[serde(default)]
+    pub game: Option<PathBuf>,
+}
+
+impl Session {
+    pub fn new(
+        id: u64,
+        board: &Board,
+        clue_collection: &ClueCollection,
+        game: Option<&Path>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        Self {
+            id,
+            timestamp,
+            to_link: board.to_link().to_vec(),
+            to_avoid: board.to_avoid().to_vec(),
+            assassins: board.assassins().to_vec(),
+            clues: clue_collection.iter().cloned().collect(),
+            usage: clue_collection
+                .usage()
+                .iter()
+                .map(|entry| ModelTokens {
+                    model: entry.model.clone(),
+                    usage: entry.usage.clone(),
+                    cached: entry.cached,
+                })
+                .collect(),
+            given: None,
+            guesses: vec![],
+            outcome: None,
+            language: clue_collection.language(),
+            // `history review` may run from another directory
+            game: game.map(|path| std::path::absolute(path).unwrap_or(path.to_path_buf())),
+        }
+    }
+
+    /// The models that were asked for clues
+    pub fn models(&self) -> Vec<&str> {
+        self.usage
+            .iter()
+            .map(|entry| entry.model.as_str())
+            .collect()
+    }
+
+    /// Whether the cards are pictures, stored as coordinates like `B3` instead of words
+    pub fn is_pictures(&self) -> bool {
+        self.to_link
+            .iter()
+            .chain(&self.to_avoid)
+            .all(|card| is_coordinate(card) && *card == card.to_uppercase())
+    }
+
+    /// Guesses that were your team's agents
+    pub fn correct_guesses(&self) -> usize {
+        self.guesses
+            .iter()
+            .filter(|guess| self.to_link.contains(guess))
+            .count()
+    }
+
+    /// Record the guesses of your team. The turn ends at the first guess that isn't one of
+    /// your agents. `is_assassin` is only asked about such a guess, for boards that don't
+    /// list their assassins.
+    pub fn record_guesses(&mut self, guesses: Vec<String>, is_assassin: impl Fn(&str) -> bool) {
+        let wrong_guess = guesses.iter().find(|guess| !self.to_link.contains(guess));
+        self.outcome = Some(match wrong_guess {
+            None => Outcome::Correct,
+            Some(guess)
+                if self.assassins.contains(guess)
+                    || (self.assassins.is_empty() && is_assassin(guess)) =>
+            {
+                Outcome::Assassin
+            }
+            Some(_) => Outcome::Avoid,
+        });
+        self.guesses = guesses;
+    }
+}
+
+/// Every session, one JSON object per line, in `history.jsonl` next to the config file
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let Some(config_dir) = dirs::config_dir() else {
+            return Err("No config directory found".into());
+        };
+
+        Ok(Self::with_path(
+            config_dir.join("mastermind").join("history.jsonl"),
+        ))
+    }
+
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read every session, oldest first
+    pub fn load(&self) -> Result<Vec<Session>, Box<dyn Error>> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return Ok(vec![]);
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    format!(
+                        "Invalid history entry at line {} of {}: {e}",
+                        index + 1,
+                        self.path.display()
+                    )
+                    .into()
+                })
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: u64) -> Result<Session, Box<dyn Error>> {
+        self.load()?
+            .into_iter()
+            .find(|session| session.id == id)
+            .ok_or_else(|| format!("No game #{id} in the history").into())
+    }
+
+    /// Store a new session and return its ID
+    pub fn record(
+        &self,
+        board: &Board,
+        clue_collection: &ClueCollection,
+        game: Option<&Path>,
+    ) -> Result<u64, Box<dyn Error>> {
+        let id = self.load()?.last().map_or(1, |session| session.id + 1);
+        let session = Session::new(id, board, clue_collection, game);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&session)?)?;
+
+        Ok(id)
+    }
+
+    /// Replace a stored session, e.g. after recording the guesses
+    pub fn update(&self, session: &Session) -> Result<(), Box<dyn Error>> {
+        let mut content = String::new();
+        for stored in self.load()? {
+            let line = if stored.id == session.id {
+                serde_json::to_string(session)?
+            } else {
+                serde_json::to_string(&stored)?
+            };
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        // Write a copy first, so that a crash can't leave the history half written
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Format seconds since the Unix epoch as a UTC date and time, e.g. `2024-11-20 18:30`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (hour, minute) = (timestamp % 86_400 / 3600, timestamp % 3600 / 60);
+    format!("{} {hour:02}:{minute:02}", format_date(timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn clue_collection() -> ClueCollection {
+        let response = serde_json::from_value(serde_json::json!({
+            "model": "model-a",
+            "choices": [{ "message": { "content": "music, 2, sound, bee\nfilm, 2, bond, tokyo" } }],
+            "usage": { "prompt_tokens": 100, "completion_tokens": 20, "total_tokens": 120 }
+        }))
+        .unwrap();
+        ClueCollection::new(vec![response])
+    }
+
+    fn board() -> Board {
+        Board::new(
+            vec!["sound".into(), "bee".into(), "bond".into(), "tokyo".into()],
+            vec!["park".into(), "penny".into()],
+        )
+    }
+
+    #[test]
+    fn test_record_and_update() {
+        let temp_dir = tempdir().unwrap();
+        let history = History::with_path(temp_dir.path().join("history.jsonl"));
+        assert!(history.load().unwrap().is_empty());
+
+        assert_eq!(
+            history.record(&board(), &clue_collection(), None).unwrap(),
+            1
+        );
+        let game = temp_dir.path().join("game.txt");
+        assert_eq!(
+            history
+                .record(&board(), &clue_collection(), Some(&game))
+                .unwrap(),
+            2
+        );
+
+        let mut session = history.get(2).unwrap();
+        assert_eq!(session.clues.len(), 2);
+        assert_eq!(session.game, Some(game));
+        assert_eq!(session.models(), ["model-a"]);
+        assert_eq!(session.usage[0].usage.total_tokens, 120);
+
+        session.given = Some(session.clues[0].clone());
+        session.record_guesses(vec!["sound".into(), "bee".into()], |_| false);
+        history.update(&session).unwrap();
+
+        let sessions = history.load().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions[0].given.is_none());
+        assert_eq!(sessions[1].given.as_ref().unwrap().clue_word(), "music");
+        assert_eq!(sessions[1].outcome, Some(Outcome::Correct));
+
+        assert!(history.get(3).is_err());
+
+        // Corrupted file
+        fs::write(history.path(), "{}\n").unwrap();
+        assert!(history.load().is_err());
+    }
+
+    #[test]
+    fn test_record_guesses() {
+        let mut session = Session::new(1, &board(), &clue_collection(), None);
+
+        session.record_guesses(vec!["sound".into(), "park".into()], |_| false);
+        assert_eq!(session.outcome, Some(Outcome::Avoid));
+        assert_eq!(session.correct_guesses(), 1);
+
+        session.record_guesses(vec!["penny".into()], |guess| guess == "penny");
+        assert_eq!(session.outcome, Some(Outcome::Assassin));
+        assert_eq!(session.correct_guesses(), 0);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_732_127_400), "2024-11-20 18:30");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+    }
+}
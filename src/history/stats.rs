@@ -0,0 +1,285 @@
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Table};
+use std::error::Error;
+
+use super::{History, Outcome, Session};
+use crate::pricing::{format_cost, PriceTable};
+
+/// How well the clues of one model worked, over the reviewed games it was asked about
+#[derive(Debug, PartialEq)]
+pub struct ModelStats {
+    pub model: String,
+    /// Reviewed games where this model suggested clues
+    pub games: usize,
+    /// Games where one of its clues was given
+    pub chosen: usize,
+    /// Correct guesses after its clues, in total
+    pub correct_guesses: usize,
+    /// Its clues that ended on a word to avoid or the assassin
+    pub avoid_hits: usize,
+    pub assassin_hits: usize,
+    /// Its clues that led to at least one correct guess
+    pub useful: usize,
+    pub total_tokens: usize,
+    /// Unknown if the model has no known price
+    pub cost: Option<f64>,
+}
+
+impl ModelStats {
+    fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            games: 0,
+            chosen: 0,
+            correct_guesses: 0,
+            avoid_hits: 0,
+            assassin_hits: 0,
+            useful: 0,
+            total_tokens: 0,
+            cost: Some(0.0),
+        }
+    }
+
+    /// Correct guesses per game the model took part in, what the ranking is based on
+    pub fn score(&self) -> f64 {
+        ratio(self.correct_guesses, self.games)
+    }
+
+    /// Correct guesses per clue of this model that was given
+    pub fn correct_per_clue(&self) -> f64 {
+        ratio(self.correct_guesses, self.chosen)
+    }
+}
+
+/// Aggregate the reviewed games per model, best first
+pub fn leaderboard(sessions: &[Session], prices: &PriceTable) -> Vec<ModelStats> {
+    let mut stats: Vec<ModelStats> = vec![];
+
+    // Games without a recorded clue say nothing about the models
+    for session in sessions.iter().filter(|session| session.given.is_some()) {
+        for entry in &session.usage {
+            let index = match stats.iter().position(|stats| stats.model == entry.model) {
+                Some(index) => index,
+                None => {
+                    stats.push(ModelStats::new(&entry.model));
+                    stats.len() - 1
+                }
+            };
+            let model_stats = &mut stats[index];
+
+            // Cached answers weren't paid for again
+            model_stats.games += 1;
+            if !entry.cached {
+                model_stats.total_tokens += entry.usage.total_tokens;
+                model_stats.cost = model_stats
+                    .cost
+                    .zip(prices.cost(&entry.model, &entry.usage))
+                    .map(|(a, b)| a + b);
+            }
+
+            let chosen = session
+                .given
+                .as_ref()
+                .is_some_and(|clue| clue.source() == entry.model);
+            if !chosen {
+                continue;
+            }
+
+            let correct_guesses = session.correct_guesses();
+            model_stats.chosen += 1;
+            model_stats.correct_guesses += correct_guesses;
+            if correct_guesses > 0 {
+                model_stats.useful += 1;
+            }
+            match session.outcome {
+                Some(Outcome::Avoid) => model_stats.avoid_hits += 1,
+                Some(Outcome::Assassin) => model_stats.assassin_hits += 1,
+                _ => {}
+            }
+        }
+    }
+
+    // Most correct guesses per game first, then the fewest bad hits
+    stats.sort_by(|a, b| {
+        b.score().total_cmp(&a.score()).then_with(|| {
+            let bad_hits =
+                |stats: &ModelStats| ratio(stats.avoid_hits + stats.assassin_hits, stats.chosen);
+            bad_hits(a).total_cmp(&bad_hits(b))
+        })
+    });
+    stats
+}
+
+/// Print the leaderboard of every model in the history
+pub fn run(prices: &PriceTable) -> Result<(), Box<dyn Error>> {
+    let stats = leaderboard(&History::new()?.load()?, prices);
+    if stats.is_empty() {
+        println!("No reviewed games yet. Record how a game went with `mastermind history review`");
+    } else {
+        println!("{}", stats_table(&stats));
+    }
+
+    Ok(())
+}
+
+fn stats_table(stats: &[ModelStats]) -> Table {
+    let mut table = Table::new();
+
+    // Set up header and styles
+    table
+        .set_header(
+            [
+                "Rank",
+                "Model",
+                "Games",
+                "Chosen",
+                "Correct per Game (ranking)",
+                "Correct per Chosen Clue",
+                "Avoid Hits",
+                "Assassin Hits",
+                "Tokens per Useful Clue",
+                "Cost per Useful Clue",
+            ]
+            .map(|header| {
+                Cell::new(header)
+                    .add_attribute(Attribute::Bold)
+                    .set_alignment(CellAlignment::Center)
+            }),
+        )
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS);
+
+    for (rank, entry) in stats.iter().enumerate() {
+        let per_useful = |value: f64| (entry.useful > 0).then(|| value / entry.useful as f64);
+        table.add_row(vec![
+            (rank + 1).to_string(),
+            entry.model.clone(),
+            entry.games.to_string(),
+            format!(
+                "{} ({:.0}%)",
+                entry.chosen,
+                100.0 * ratio(entry.chosen, entry.games)
+            ),
+            format!("{:.1}", entry.score()),
+            format!("{:.1}", entry.correct_per_clue()),
+            format!("{:.0}%", 100.0 * ratio(entry.avoid_hits, entry.chosen)),
+            format!("{:.0}%", 100.0 * ratio(entry.assassin_hits, entry.chosen)),
+            per_useful(entry.total_tokens as f64)
+                .map_or("-".to_string(), |tokens| format!("{tokens:.0}")),
+            match entry.cost {
+                Some(cost) => per_useful(cost).map_or("-".to_string(), format_cost),
+                None => "unknown".to_string(),
+            },
+        ]);
+    }
+
+    // Right-align the numbers
+    for index in 2..10 {
+        table
+            .column_mut(index)
+            .expect("The table should have 10 columns")
+            .set_cell_alignment(CellAlignment::Right);
+    }
+
+    table
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::clues::clue_collection::ClueCollection;
+
+    /// A game where both models were asked, and the clue of `given` was given
+    fn session(given: &str, guesses: &[&str]) -> Session {
+        let response = |model: &str, content: &str| {
+            serde_json::from_value(serde_json::json!({
+                "model": model,
+                "choices": [{ "message": { "content": content } }],
+                "usage": { "prompt_tokens": 100, "completion_tokens": 50, "total_tokens": 150 }
+            }))
+            .unwrap()
+        };
+        let clue_collection = ClueCollection::new(vec![
+            response("gpt-4o", "music, 2, sound, bee"),
+            response("local-model", "film, 2, bond, tokyo"),
+        ]);
+        let board = Board::new(
+            vec!["sound".into(), "bee".into(), "bond".into(), "tokyo".into()],
+            vec!["park".into(), "penny".into()],
+        );
+
+        let mut session = Session::new(1, &board, &clue_collection, None);
+        session.given = session
+            .clues
+            .iter()
+            .find(|clue| clue.source() == given)
+            .cloned();
+        session.record_guesses(
+            guesses.iter().map(|guess| guess.to_string()).collect(),
+            |_| false,
+        );
+        session
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let mut unreviewed = session("gpt-4o", &[]);
+        unreviewed.given = None;
+        let sessions = vec![
+            session("gpt-4o", &["sound", "bee"]),
+            session("gpt-4o", &["sound", "park"]),
+            session("local-model", &["penny"]),
+            unreviewed,
+        ];
+
+        let stats = leaderboard(&sessions, &PriceTable::default());
+        assert_eq!(stats.len(), 2);
+
+        let best = &stats[0];
+        assert_eq!(best.model, "gpt-4o");
+        assert_eq!(best.games, 3);
+        assert_eq!(best.chosen, 2);
+        assert_eq!(best.correct_guesses, 3);
+        assert_eq!(best.useful, 2);
+        assert_eq!(best.avoid_hits, 1);
+        assert_eq!(best.correct_per_clue(), 1.5);
+        assert_eq!(best.total_tokens, 450);
+        assert!(best.cost.is_some());
+
+        let worst = &stats[1];
+        assert_eq!(worst.model, "local-model");
+        assert_eq!(worst.chosen, 1);
+        assert_eq!(worst.useful, 0);
+        assert_eq!(worst.avoid_hits, 1);
+        assert_eq!(worst.cost, None);
+
+        let table = stats_table(&stats).to_string();
+        assert!(table.contains("2 (67%)"));
+        assert!(table.contains("Correct per Game (ranking)"));
+        assert!(table.contains("1.5"));
+        assert!(table.contains("unknown"));
+
+        // Cached answers count as games, but their tokens weren't spent again
+        let mut cached = session("gpt-4o", &["sound"]);
+        for entry in &mut cached.usage {
+            entry.cached = true;
+        }
+        let stats = leaderboard(
+            &[session("gpt-4o", &["bee"]), cached],
+            &PriceTable::default(),
+        );
+        assert_eq!(stats[0].games, 2);
+        assert_eq!(stats[0].total_tokens, 150);
+    }
+}
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Deserialize)]
 pub struct Message {
@@ -10,16 +11,38 @@ pub struct Choice {
     pub message: Message,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
 pub struct Usage {
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
     pub total_tokens: usize,
 }
 
+/// Measured by the client, not part of the API response
+///
+/// ```
+/// use mastermind_cli::{ChatCompletionsResponse, Timing};
+///
+/// let response: ChatCompletionsResponse = serde_json::from_str(
+///     r#"{"model": "model", "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}, "choices": []}"#,
+/// )
+/// .unwrap();
+/// let Timing { latency, cached } = response.timing;
+/// assert!(latency.is_zero() && !cached);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Timing {
+    /// From sending the request to receiving the whole response
+    pub latency: Duration,
+    /// Whether the response came from the cache instead of the API
+    pub cached: bool,
+}
+
 #[derive(Deserialize)]
 pub struct ChatCompletionsResponse {
     pub model: String,
     pub usage: Usage,
     pub choices: Vec<Choice>,
+    #[serde(skip)]
+    pub timing: Timing,
 }
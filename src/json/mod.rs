@@ -1,2 +1,2 @@
-pub(crate) mod chat_completions;
-pub(crate) mod models;
+pub mod chat_completions;
+pub mod models;
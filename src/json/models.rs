@@ -1,8 +1,25 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize)]
+/// A model listed by the API server
+///
+/// ```
+/// use mastermind_cli::{Model, ModelCollection, ModelsResponse};
+///
+/// let response: ModelsResponse =
+///     serde_json::from_str(r#"{"data": [{"id": "model", "context_window": 8192}]}"#).unwrap();
+/// let model_collection = ModelCollection::new(&response);
+/// let models: Vec<&Model> = model_collection.iter().collect();
+/// assert_eq!(models[0].id, "model");
+/// assert_eq!(models[0].context_length, Some(8192));
+/// ```
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Model {
     pub id: String,
+    pub owned_by: Option<String>,
+    pub created: Option<u64>,
+    /// Not part of the OpenAI spec, but many providers include it under one of these names
+    #[serde(alias = "context_window", alias = "max_context_length")]
+    pub context_length: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -0,0 +1,300 @@
+use caseless::default_case_fold_str;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Language of the board, the prompt and the clues
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub enum Language {
+    #[default]
+    #[value(name = "en")]
+    #[serde(rename = "en")]
+    English,
+    #[value(name = "de")]
+    #[serde(rename = "de")]
+    German,
+    #[value(name = "fr")]
+    #[serde(rename = "fr")]
+    French,
+    #[value(name = "es")]
+    #[serde(rename = "es")]
+    Spanish,
+    #[value(name = "tr")]
+    #[serde(rename = "tr")]
+    Turkish,
+}
+
+/// The localized parts of the prompt
+pub struct Template {
+    /// With placeholders for `ClueRules::fill`
+    pub system_prompt: &'static str,
+    pub to_link: &'static str,
+    pub to_avoid: &'static str,
+    pub assassins: &'static str,
+    pub used_clues: &'static str,
+    pub targets: &'static str,
+    pub end_game: &'static str,
+}
+
+const ENGLISH: Template = Template {
+    system_prompt: "
+You are the spymaster in Codenames.
+I will give you a list of [agent word], followed by a list of [avoid word].
+Try to link [agent word] together.
+Tro to avoid [avoid word].
+Answer in this format:
+[clue word], [number of agent words], [agent word], [agent word], [agent word]
+...
+Here are the requirements:
+- Always answer in lower case.
+- No special characters.
+- No intro or outro.
+- No explanations.
+- Give {min_clues}-{max_clues} [clue word].
+- Each [clue word] should link at least {min_linked} [agent word].
+",
+    to_link: "To Link:",
+    to_avoid: "To Avoid:",
+    assassins: "Assassins (a clue that relates to any of these loses the game):",
+    used_clues: "Clues already given in this game (never give them again):",
+    targets: "Every clue must link all of these words, and as few other words as possible:",
+    end_game: "Only a few agent words are left. A clue for a single [agent word] is fine, as long as it can't be mistaken for an [avoid word].",
+};
+
+const GERMAN: Template = Template {
+    system_prompt: "
+Du bist der Geheimdienstchef in Codenames.
+Ich gebe dir eine Liste von [Agentenwort], gefolgt von einer Liste von [Vermeidungswort].
+Versuche, [Agentenwort] miteinander zu verbinden.
+Versuche, [Vermeidungswort] zu vermeiden.
+Antworte in diesem Format:
+[Hinweiswort], [Anzahl der Agentenwörter], [Agentenwort], [Agentenwort], [Agentenwort]
+...
+Hier sind die Anforderungen:
+- Antworte immer auf Deutsch und in Kleinbuchstaben.
+- Keine Sonderzeichen außer Bindestrichen.
+- Keine Einleitung und kein Schluss.
+- Keine Erklärungen.
+- Gib {min_clues}-{max_clues} [Hinweiswort].
+- Jedes [Hinweiswort] sollte mindestens {min_linked} [Agentenwort] verbinden.
+",
+    to_link: "Verbinden:",
+    to_avoid: "Vermeiden:",
+    assassins: "Attentäter (ein Hinweis, der zu einem dieser Wörter passt, verliert das Spiel):",
+    used_clues: "Bereits in diesem Spiel gegebene Hinweise (nie wiederverwenden):",
+    targets:
+        "Jeder Hinweis muss alle diese Wörter verbinden, und so wenige andere Wörter wie möglich:",
+    end_game: "Es sind nur noch wenige Agentenwörter übrig. Ein Hinweis für ein einziges [Agentenwort] ist in Ordnung, solange er nicht mit einem [Vermeidungswort] verwechselt werden kann.",
+};
+
+const FRENCH: Template = Template {
+    system_prompt: "
+Tu es le maître-espion dans Codenames.
+Je vais te donner une liste de [mot agent], suivie d'une liste de [mot à éviter].
+Essaie de relier les [mot agent] entre eux.
+Essaie d'éviter les [mot à éviter].
+Réponds dans ce format :
+[mot indice], [nombre de mots agents], [mot agent], [mot agent], [mot agent]
+...
+Voici les exigences :
+- Réponds toujours en français et en minuscules.
+- Pas de caractères spéciaux, sauf les apostrophes et les traits d'union.
+- Pas d'introduction ni de conclusion.
+- Pas d'explications.
+- Donne {min_clues} à {max_clues} [mot indice].
+- Chaque [mot indice] doit relier au moins {min_linked} [mot agent].
+",
+    to_link: "À relier :",
+    to_avoid: "À éviter :",
+    assassins: "Assassins (un indice lié à l'un de ces mots fait perdre la partie) :",
+    used_clues: "Indices déjà donnés dans cette partie (ne jamais les réutiliser) :",
+    targets: "Chaque indice doit relier tous ces mots, et le moins d'autres mots possible :",
+    end_game: "Il ne reste que quelques mots agents. Un indice pour un seul [mot agent] convient, tant qu'il ne peut pas être confondu avec un [mot à éviter].",
+};
+
+const SPANISH: Template = Template {
+    system_prompt: "
+Eres el jefe de espías en Codenames.
+Te daré una lista de [palabra agente], seguida de una lista de [palabra a evitar].
+Intenta relacionar las [palabra agente] entre sí.
+Intenta evitar las [palabra a evitar].
+Responde en este formato:
+[palabra pista], [número de palabras agente], [palabra agente], [palabra agente], [palabra agente]
+...
+Estos son los requisitos:
+- Responde siempre en español y en minúsculas.
+- Sin caracteres especiales.
+- Sin introducción ni conclusión.
+- Sin explicaciones.
+- Da de {min_clues} a {max_clues} [palabra pista].
+- Cada [palabra pista] debe relacionar al menos {min_linked} [palabra agente].
+",
+    to_link: "Relacionar:",
+    to_avoid: "Evitar:",
+    assassins:
+        "Asesinos (una pista relacionada con cualquiera de estas palabras pierde la partida):",
+    used_clues: "Pistas ya dadas en esta partida (no las repitas nunca):",
+    targets: "Cada pista debe unir todas estas palabras, y el menor número posible de otras:",
+    end_game: "Quedan pocas palabras agente. Una pista para una sola [palabra agente] está bien, siempre que no se pueda confundir con una [palabra a evitar].",
+};
+
+const TURKISH: Template = Template {
+    system_prompt: "
+Codenames oyununda casus şefisin.
+Sana bir [ajan kelime] listesi, ardından bir [kaçınılacak kelime] listesi vereceğim.
+[ajan kelime] listesindeki kelimeleri birbirine bağlamaya çalış.
+[kaçınılacak kelime] listesindeki kelimelerden kaçın.
+Bu biçimde cevap ver:
+[ipucu kelime], [ajan kelime sayısı], [ajan kelime], [ajan kelime], [ajan kelime]
+...
+Gereksinimler:
+- Her zaman Türkçe ve küçük harflerle cevap ver.
+- Özel karakter kullanma.
+- Giriş veya kapanış yazma.
+- Açıklama yapma.
+- {min_clues}-{max_clues} [ipucu kelime] ver.
+- Her [ipucu kelime] en az {min_linked} [ajan kelime] bağlamalı.
+",
+    to_link: "Bağlanacaklar:",
+    to_avoid: "Kaçınılacaklar:",
+    assassins: "Suikastçılar (bunlardan biriyle ilgili bir ipucu oyunu kaybettirir):",
+    used_clues: "Bu oyunda daha önce verilen ipuçları (asla tekrar kullanma):",
+    targets: "Her ipucu bu kelimelerin hepsini bağlamalı, başka kelimeleri ise olabildiğince az:",
+    end_game: "Sadece birkaç ajan kelime kaldı. Tek bir [ajan kelime] için ipucu vermek, bir [kaçınılacak kelime] ile karıştırılamadığı sürece uygundur.",
+};
+
+impl Language {
+    /// The system prompt and the labels of the word lists
+    pub fn template(self) -> &'static Template {
+        match self {
+            Language::English => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::French => &FRENCH,
+            Language::Spanish => &SPANISH,
+            Language::Turkish => &TURKISH,
+        }
+    }
+
+    /// The name of the language, in English
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "German",
+            Language::French => "French",
+            Language::Spanish => "Spanish",
+            Language::Turkish => "Turkish",
+        }
+    }
+
+    /// Headers of the clue table: clue, count, linked words and source
+    pub fn table_headers(self) -> [&'static str; 4] {
+        match self {
+            Language::English => ["Clue", "Count", "Linked Words", "Source"],
+            Language::German => ["Hinweis", "Anzahl", "Verbundene Wörter", "Quelle"],
+            Language::French => ["Indice", "Nombre", "Mots reliés", "Source"],
+            Language::Spanish => ["Pista", "Cantidad", "Palabras relacionadas", "Fuente"],
+            Language::Turkish => ["İpucu", "Sayı", "Bağlanan Kelimeler", "Kaynak"],
+        }
+    }
+
+    /// Punctuation that belongs inside words, like hyphens in German compounds
+    /// or apostrophes in French elisions
+    fn word_punctuation(self) -> &'static [char] {
+        match self {
+            Language::German => &['-'],
+            Language::French => &['\'', '’', '-'],
+            Language::English | Language::Spanish | Language::Turkish => &[],
+        }
+    }
+
+    /// Check that a clue or linked word only contains letters, spaces and the punctuation
+    /// of this language. Combining marks are accepted for scripts that need them.
+    pub fn is_word(self, text: &str) -> bool {
+        text.chars().all(|c| {
+            c.is_alphabetic()
+                || c == ' '
+                || is_combining_mark(c)
+                || self.word_punctuation().contains(&c)
+        })
+    }
+
+    /// Strip byte order marks, collapse whitespace and apply NFC normalization. The spelling
+    /// is kept for display, compare words with `same_word`.
+    pub fn normalize(self, word: &str) -> String {
+        word.replace('\u{feff}', "")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .nfc()
+            .collect()
+    }
+
+    /// Whether two words are the same once case folded, e.g. `Straße` and `STRASSE`
+    pub fn same_word(self, a: &str, b: &str) -> bool {
+        self.fold(a) == self.fold(b)
+    }
+
+    /// Case fold a word following the rules of this language, e.g. `I` becomes `ı` in Turkish.
+    /// The result is a comparison key, not meant for display.
+    pub fn fold(self, text: &str) -> String {
+        let folded = match self {
+            Language::Turkish => default_case_fold_str(
+                &text
+                    .chars()
+                    .map(|c| match c {
+                        'I' => 'ı',
+                        'İ' => 'i',
+                        _ => c,
+                    })
+                    .collect::<String>(),
+            ),
+            _ => default_case_fold_str(text),
+        };
+        folded.nfc().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_word() {
+        assert!(Language::English.is_word("scuba diver"));
+        assert!(!Language::English.is_word("aujourd'hui"));
+        assert!(Language::French.is_word("aujourd'hui"));
+        assert!(Language::French.is_word("l’eau"));
+        assert!(Language::German.is_word("Schiff-Fahrt"));
+        assert!(!Language::German.is_word("ge#ar"));
+        // Devanagari vowel signs are combining marks
+        assert!(Language::English.is_word("हिंदी"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            Language::Turkish.normalize(" \u{feff}IRMAK  Kıyısı "),
+            "IRMAK Kıyısı"
+        );
+        // Composed to NFC, the spelling is kept
+        assert_eq!(Language::German.normalize("Strasse\u{301}"), "Strassé");
+        assert_eq!(Language::German.normalize("Straße"), "Straße");
+    }
+
+    #[test]
+    fn test_same_word() {
+        assert!(Language::German.same_word("Straße", "STRASSE"));
+        assert!(Language::Turkish.same_word("IRMAK", "ırmak"));
+        assert!(!Language::English.same_word("IRMAK", "ırmak"));
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(Language::English.fold("ISTANBUL"), "istanbul");
+        assert_eq!(Language::Turkish.fold("ISPARTA"), "ısparta");
+        assert_eq!(Language::Turkish.fold("İSTANBUL"), "istanbul");
+        // Full case folding, so every spelling of a word compares equal
+        assert_eq!(Language::German.fold("STRAẞE"), "strasse");
+        assert_eq!(Language::German.fold("Straße"), "strasse");
+    }
+}
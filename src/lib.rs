@@ -1,49 +1,491 @@
-use clap::Parser;
+//! Mastermind - An LLM-powered CLI tool to help you be a better spymaster in Codenames
+//!
+//! The crate can also be used as a library:
+//!
+//! ```no_run
+//! use mastermind_cli::language::Language;
+//! use mastermind_cli::{Board, GenerateOptions, Instance};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let api_instance = Instance::builder("https://api.groq.com/openai/v1/")
+//!     .api_key("your API key")
+//!     .build();
+//!
+//! let board = Board::new(
+//!     vec!["sound".to_string(), "bee".to_string()],
+//!     vec!["park".to_string(), "penny".to_string()],
+//! );
+//! let model_ids = vec!["llama-3.1-70b-versatile".to_string()];
+//! let options = GenerateOptions::default()
+//!     .language(Language::English)
+//!     .used_clues(vec!["music".to_string()]);
+//!
+//! let clue_collection = api_instance
+//!     .generate_clues(&board, &model_ids, &options)
+//!     .await?;
+//! for clue in &clue_collection {
+//!     println!("{} {}: {}", clue.clue_word(), clue.count(), clue.linked_words().join(", "));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use clap::{Parser, Subcommand};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use board::grid::Grid;
+use board::structured::Format;
+use board::Team;
+use clues::rules::ClueRules;
+use configs::config::Config;
+use generation::GenerationParams;
+use language::Language;
 
 pub mod api;
+pub mod board;
+pub mod cache;
 pub mod clues;
 pub mod configs;
+pub mod generation;
+mod history;
+mod json;
+pub mod language;
 pub mod model_collection;
+pub mod pricing;
+mod server;
+mod tui;
 
-mod json;
+pub use api::{GenerateOptions, Instance};
+pub use board::Board;
+pub use clues::clue::Clue;
+pub use clues::clue_collection::ClueCollection;
+pub use json::chat_completions::{ChatCompletionsResponse, Choice, Message, Timing, Usage};
+pub use json::models::{Model, ModelsResponse};
+pub use model_collection::ModelCollection;
+
+// Used by the `mastermind` binary, not part of the library API
+#[doc(hidden)]
+pub use history::{command::run as run_history_command, stats::run as print_stats, History};
+#[doc(hidden)]
+pub use server::{serve, ServerState};
+#[doc(hidden)]
+pub use tui::{run as run_tui, App};
 
 /// Mastermind - An LLM-powered CLI tool to help you be a better spymaster in Codenames
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Print all available language models
     #[arg(short, long = "get-models")]
     pub get: bool,
 
+    /// Only print models whose IDs contain this pattern
+    #[arg(long, value_name = "PATTERN", requires = "get")]
+    pub filter: Option<String>,
+
+    /// Also print models that can't produce clues (embedding, speech, image, etc.)
+    #[arg(long = "all-models", requires = "get")]
+    pub all_models: bool,
+
     /// Select language model(s)
     #[arg(short, long = "set-models", default_missing_value = "interactive", num_args = 0..)]
     pub models: Option<Vec<String>>,
 
+    /// Save the selected model(s) as the new default
+    #[arg(short, long = "save-default", requires = "models")]
+    pub save_default: bool,
+
     /// Specify an output file
     #[arg(short, long, value_name = "FILE")]
     pub output: Option<PathBuf>,
 
-    /// Print token usage information
+    /// Always send requests to the API, without reading or writing the cache
+    #[arg(long = "no-cache", conflicts_with = "refresh")]
+    pub no_cache: bool,
+
+    /// Ignore cached responses and replace them with new ones
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Explore the clues on a full-screen view of the board
+    #[arg(long, conflicts_with_all = ["get", "output"])]
+    pub tui: bool,
+
+    /// Don't record this game in the history
+    #[arg(long = "no-history")]
+    pub no_history: bool,
+
+    /// Print token usage and cost information
     #[arg(short, long = "token-usage")]
     pub token: bool,
 
-    /// File containing words to link together - the words from your team
-    #[arg(required_unless_present = "get")]
+    /// Refuse to send requests whose estimated prompt cost exceeds this amount in USD
+    #[arg(long = "max-cost", value_name = "USD")]
+    pub max_cost: Option<f64>,
+
+    /// Language of the board and the clues. Defaults to the `clues.language` setting, then English
+    #[arg(long, value_enum)]
+    pub language: Option<Language>,
+
+    /// Comma-separated clues already given in this game, which won't be suggested again
+    #[arg(long = "used-clues", value_name = "WORDS")]
+    pub used_clues: Option<String>,
+
+    /// Fewest clues to ask each model for. Defaults to the `clues.min-clues` setting, then 5
+    #[arg(long = "min-clues", value_name = "COUNT")]
+    pub min_clues: Option<usize>,
+
+    /// Most clues to ask each model for. Defaults to the `clues.max-clues` setting, then 10
+    #[arg(long = "max-clues", value_name = "COUNT")]
+    pub max_clues: Option<usize>,
+
+    /// Fewest words each clue must link. Defaults to the `clues.min-linked` setting, then 2
+    #[arg(long = "min-linked", value_name = "COUNT")]
+    pub min_linked: Option<usize>,
+
+    /// Allow clues for a single word once this many words to link are left, 0 to never allow them.
+    /// Defaults to the `clues.end-game` setting, then 2
+    #[arg(long = "end-game", value_name = "COUNT")]
+    pub end_game: Option<usize>,
+
+    /// Comma-separated words to link that every clue must cover, e.g. the ones you want guessed this turn
+    #[arg(long, value_name = "WORDS")]
+    pub target: Option<String>,
+
+    /// File listing the clues already given in this game, one per line. `history review` adds the clue you gave
+    #[arg(long, value_name = "FILE")]
+    pub game: Option<PathBuf>,
+
+    /// File containing words to link together - the words from your team. Use - for stdin
+    #[arg(
+        required_unless_present_any = ["get", "link", "board"],
+        conflicts_with_all = ["link", "board"]
+    )]
     pub to_link: Option<PathBuf>,
 
-    /// File containing words to avoid - opponent's words, neutral words, and the assassin word
-    #[arg(required_unless_present = "get")]
+    /// File containing words to avoid - opponent's words, neutral words, and the assassin word. Use - for stdin
+    #[arg(
+        required_unless_present_any = ["get", "avoid", "board"],
+        conflicts_with_all = ["avoid", "board"]
+    )]
     pub to_avoid: Option<PathBuf>,
+
+    /// Comma-separated words to link together, instead of TO_LINK
+    #[arg(long, value_name = "WORDS", requires = "avoid")]
+    pub link: Option<String>,
+
+    /// Comma-separated words to avoid, instead of TO_AVOID
+    #[arg(long, value_name = "WORDS", requires = "link")]
+    pub avoid: Option<String>,
+
+    /// JSON, TOML, YAML or .board grid file describing every card on the board, instead of TO_LINK and TO_AVOID. Use - for stdin
+    #[arg(short, long, value_name = "FILE", conflicts_with_all = ["link", "avoid"])]
+    pub board: Option<PathBuf>,
+
+    #[command(flatten, next_help_heading = "Generation")]
+    pub generation: GenerationParams,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// View, change and validate settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// Manage cached responses
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+
+    /// Create and manage boards
+    Board {
+        #[command(subcommand)]
+        action: BoardCommand,
+    },
+
+    /// Review past games and the clues that worked
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+
+    /// Rank the models by how well their clues worked in reviewed games
+    Stats,
+
+    /// Serve clue generation as a REST API
+    Serve {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        bind: SocketAddr,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BoardCommand {
+    /// Draw a random board for a practice game. Prints it as a grid unless an output is given
+    New {
+        /// Word list to draw from, one word per line. Defaults to the bundled list
+        #[arg(short, long, value_name = "FILE")]
+        words: Option<PathBuf>,
+
+        /// Seed of the random number generator, to get the same board again
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Your team
+        #[arg(short, long, value_enum, default_value_t = Team::Red)]
+        team: Team,
+
+        /// Write the board as a grid file
+        #[arg(short, long, value_name = "FILE", conflicts_with_all = ["link", "avoid"])]
+        output: Option<PathBuf>,
+
+        /// Write the words to link to this file
+        #[arg(long, value_name = "FILE", requires = "avoid")]
+        link: Option<PathBuf>,
+
+        /// Write the words to avoid to this file
+        #[arg(long, value_name = "FILE", requires = "link")]
+        avoid: Option<PathBuf>,
+    },
+
+    /// Read the words of a board from a photo with a vision model, then confirm them
+    /// and pick the role of each card
+    FromImage {
+        /// Photo of the board, as PNG, JPEG, GIF or WebP
+        photo: PathBuf,
+
+        /// Vision-capable model to read the photo with. Defaults to the first default model
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Your team
+        #[arg(short, long, value_enum, default_value_t = Team::Red)]
+        team: Team,
+
+        /// Language of the words on the cards. Defaults to the `clues.language` setting, then English
+        #[arg(long, value_enum)]
+        language: Option<Language>,
+
+        /// Write the board as a grid file
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+
+    /// Spend one of the shared timer tokens of a Duet board at the end of a turn
+    EndTurn {
+        /// Duet board file, updated in place
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// List recorded games, most recent first
+    List {
+        /// Number of games to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Print the board, the clues and the outcome of a game
+    Show { id: u64 },
+
+    /// Record the clue you gave and the guesses of your team. Defaults to the latest game
+    Review { id: Option<u64> },
+
+    /// Write every recorded game to a file or stdout
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+
+        /// Specify an output file
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    /// One JSON object per game
+    Jsonl,
+    /// One row per clue
+    Csv,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Remove all cached responses
+    Clear,
+
+    /// Print the number and size of cached responses
+    Stats,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Run the interactive setup wizard
+    Init,
+
+    /// Print the value of a setting
+    Get { key: String },
+
+    /// Change the value of a setting
+    Set { key: String, value: String },
+
+    /// Remove a setting
+    Unset { key: String },
+
+    /// Print all settings (API keys are masked)
+    List,
+
+    /// Print the location of the config file
+    Path,
+
+    /// Open the config file in your editor
+    Edit,
+
+    /// Check the settings and the connection to your provider
+    Doctor,
+}
+
+/// Build the board from whichever inputs were given: a board file, inline lists or two word files.
+/// The words are normalized following the rules of `language`.
+pub fn read_board(args: &Args, language: Language) -> Result<Board, Box<dyn std::error::Error>> {
+    let mut board = read_board_words(args)?;
+    for warning in board.normalize(language)? {
+        eprintln!("Warning: {warning}");
+    }
+
+    Ok(board)
+}
+
+fn read_board_words(args: &Args) -> Result<Board, Box<dyn std::error::Error>> {
+    if let Some(path) = &args.board {
+        return read_board_file(path);
+    }
+
+    if args.to_link.as_deref().is_some_and(is_stdin)
+        && args.to_avoid.as_deref().is_some_and(is_stdin)
+    {
+        return Err("Only one file can be read from stdin".into());
+    }
+
+    let to_link = match (&args.link, &args.to_link) {
+        (Some(words), _) => split_list(words),
+        (None, Some(path)) => read_words_from_file(path)?,
+        (None, None) => return Err("No words to link".into()),
+    };
+    let to_avoid = match (&args.avoid, &args.to_avoid) {
+        (Some(words), _) => split_list(words),
+        (None, Some(path)) => read_words_from_file(path)?,
+        (None, None) => return Err("No words to avoid".into()),
+    };
+
+    Ok(Board::new(to_link, to_avoid))
+}
+
+fn read_board_file(path: &PathBuf) -> Result<Board, Box<dyn std::error::Error>> {
+    let contents = read_to_string(path)?;
+    let format = Format::from_path(path).unwrap_or_else(|| Format::detect(&contents));
+
+    // Images of Pictures boards are found next to the board file
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let board = board::structured::parse(&contents, format, base_dir)
+        .map_err(|e| format!("{e} ({})", path.to_string_lossy()))?;
+    Ok(board)
+}
+
+/// The grid behind `--board`, when it is a `.board` grid file, to show the role of every card
+pub fn read_grid(args: &Args) -> Option<Grid> {
+    let path = args.board.as_ref().filter(|path| !is_stdin(path))?;
+    let contents = fs::read_to_string(path).ok()?;
+    let format = Format::from_path(path).unwrap_or_else(|| Format::detect(&contents));
+    if format != Format::Grid || board::duet::is_duet(&contents) {
+        return None;
+    }
+
+    Grid::parse(&contents).ok()
+}
+
+/// Split a comma-separated list, e.g. words from `--link` or model IDs from `DEFAULT_MODEL_ID`
+pub fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Clue rules from the command line, falling back on the ones from the config file
+pub fn read_clue_rules(args: &Args, config: &Config) -> Result<ClueRules, String> {
+    let defaults = config.get_clue_rules();
+    let rules = ClueRules {
+        min_clues: args.min_clues.unwrap_or(defaults.min_clues),
+        max_clues: args.max_clues.unwrap_or(defaults.max_clues),
+        min_linked: args.min_linked.unwrap_or(defaults.min_linked),
+        end_game: args.end_game.unwrap_or(defaults.end_game),
+    };
+    rules.validate()?;
+
+    Ok(rules)
+}
+
+/// Clues already given in this game, from `--used-clues` and the `--game` file
+pub fn read_used_clues(args: &Args) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut used_clues = args
+        .used_clues
+        .as_deref()
+        .map(split_list)
+        .unwrap_or_default();
+
+    // The game file doesn't exist before the first clue is given
+    if let Some(path) = args.game.as_ref().filter(|path| path.exists()) {
+        let contents = read_to_string(path)?;
+        used_clues.extend(
+            contents
+                .lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty()),
+        );
+    }
+
+    Ok(used_clues)
+}
+
+/// Add a clue to the `--game` file, creating it for the first clue of the game
+pub fn append_used_clue(path: &Path, clue_word: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|_| format!("Failed to open file: {}", path.to_string_lossy()))?;
+    writeln!(file, "{clue_word}")?;
+
+    Ok(())
 }
 
 pub fn read_words_from_file(path: &PathBuf) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    let contents = fs::read_to_string(path)
-        .map_err(|_| format!("Cannot find file: {}", path.to_string_lossy()))?;
+    let contents = read_to_string(path)?;
 
+    // Accept any line ending, including a lone carriage return
     let words: Vec<String> = contents
-        .lines()
+        .trim_start_matches('\u{feff}')
+        .split(['\n', '\r'])
         .map(|line| line.trim().to_string())
         .filter(|line| !line.is_empty())
         .collect();
@@ -55,6 +497,25 @@ pub fn read_words_from_file(path: &PathBuf) -> Result<Vec<String>, Box<dyn std::
     }
 }
 
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Read a file, or stdin if the path is `-`
+fn read_to_string(path: &PathBuf) -> Result<String, Box<dyn std::error::Error>> {
+    if is_stdin(path) {
+        let mut contents = String::new();
+        io::stdin()
+            .read_to_string(&mut contents)
+            .map_err(|e| format!("Failed to read from stdin: {e}"))?;
+        return Ok(contents);
+    }
+
+    let contents = fs::read_to_string(path)
+        .map_err(|_| format!("Cannot find file: {}", path.to_string_lossy()))?;
+    Ok(contents)
+}
+
 pub fn write_content_to_file(
     path: &PathBuf,
     content: String,
@@ -82,6 +543,104 @@ mod tests {
         assert!(to_link.is_ok());
         let to_avoid = read_words_from_file(&PathBuf::from("examples/avoid.txt"));
         assert!(to_avoid.is_ok());
+
+        // Byte order mark and mixed line endings
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("words.txt");
+        fs::write(&path, "\u{feff}bond\r\nsound\rpark\n").unwrap();
+        assert_eq!(
+            read_words_from_file(&path).unwrap(),
+            ["bond", "sound", "park"]
+        );
+    }
+
+    #[test]
+    fn test_read_board() {
+        // Two files
+        let args = Args::parse_from(["mastermind", "examples/link.txt", "examples/avoid.txt"]);
+        let board = read_board(&args, Language::English).unwrap();
+        assert_eq!(
+            board.to_link(),
+            read_words_from_file(&PathBuf::from("examples/link.txt")).unwrap()
+        );
+
+        // Inline lists
+        let args = Args::parse_from(["mastermind", "--link", "sound, bee,", "--avoid", "park"]);
+        let board = read_board(&args, Language::English).unwrap();
+        assert_eq!(board.to_link(), ["sound", "bee"]);
+        assert_eq!(board.to_avoid(), ["park"]);
+
+        // The same word on both sides
+        let args = Args::parse_from(["mastermind", "--link", "Park", "--avoid", "park"]);
+        assert!(read_board(&args, Language::English).is_err());
+
+        // Turkish words are compared with the dotless ı, and keep their spelling
+        let args = Args::parse_from(["mastermind", "--link", "IRMAK", "--avoid", "irmak"]);
+        let board = read_board(&args, Language::Turkish).unwrap();
+        assert_eq!(board.to_link(), ["IRMAK"]);
+        let args = Args::parse_from(["mastermind", "--link", "IRMAK", "--avoid", "ırmak"]);
+        assert!(read_board(&args, Language::Turkish).is_err());
+
+        // A board file
+        let args = Args::parse_from(["mastermind", "--board", "examples/board.toml"]);
+        let board = read_board(&args, Language::English).unwrap();
+        assert_eq!(board.to_link().len(), 9);
+        assert_eq!(board.to_avoid().len(), 16);
+
+        // A Pictures board, with images next to the board file
+        let args = Args::parse_from([
+            "mastermind",
+            "--board",
+            "resources/tests/pictures/board.pictures",
+        ]);
+        let board = read_board(&args, Language::English).unwrap();
+        assert_eq!(board.to_link()[0], "A1");
+        assert_eq!(board.pictures().len(), 19);
+
+        // Only grid files have roles for every card
+        let args = Args::parse_from(["mastermind", "--board", "examples/grid.board"]);
+        assert_eq!(read_grid(&args).unwrap().cards.len(), 25);
+        let args = Args::parse_from(["mastermind", "--board", "examples/duet.board"]);
+        assert!(read_grid(&args).is_none());
+
+        // Both files from stdin
+        let args = Args::parse_from(["mastermind", "-", "-"]);
+        assert!(read_board(&args, Language::English).is_err());
+
+        // Mixing inputs
+        assert!(Args::try_parse_from(["mastermind", "--link", "a", "examples/avoid.txt"]).is_err());
+        assert!(Args::try_parse_from(["mastermind", "--link", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_read_used_clues() {
+        let temp_dir = tempdir().unwrap();
+        let game = temp_dir.path().join("game.txt");
+        let args = |game: &PathBuf| {
+            Args::parse_from([
+                "mastermind",
+                "examples/link.txt",
+                "examples/avoid.txt",
+                "--used-clues",
+                "music, film",
+                "--game",
+                game.to_str().unwrap(),
+            ])
+        };
+
+        // The game file is created once the first clue is given
+        assert_eq!(read_used_clues(&args(&game)).unwrap(), ["music", "film"]);
+        append_used_clue(&game, "ocean").unwrap();
+        assert_eq!(
+            read_used_clues(&args(&game)).unwrap(),
+            ["music", "film", "ocean"]
+        );
+
+        fs::write(&game, "ocean\n\n  space \n").unwrap();
+        assert_eq!(
+            read_used_clues(&args(&game)).unwrap(),
+            ["music", "film", "ocean", "space"]
+        );
     }
 
     #[test]
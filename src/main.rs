@@ -2,24 +2,95 @@ use clap::Parser;
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
+use std::fs;
+use std::time::Duration;
 
 use mastermind_cli::{
-    api, clues, configs, model_collection, read_words_from_file, write_content_to_file, Args,
+    api, board, cache, clues, configs, generation, model_collection, pricing, print_stats,
+    read_board, read_clue_rules, read_grid, read_used_clues, read_words_from_file,
+    run_history_command, run_tui, serve, split_list, write_content_to_file, App, Args, Board,
+    BoardCommand, CacheCommand, Command, ConfigCommand, GenerateOptions, History, ServerState,
 };
 
+use board::duet::DuetKey;
+use board::generator::{self, DEFAULT_WORDS};
+use board::review;
+use cache::{Cache, CacheMode, DEFAULT_TTL};
 use clues::clue_collection::ClueCollection;
 use configs::config::Config;
+use generation::GenerationTable;
 use model_collection::ModelCollection;
+use pricing::PriceTable;
 
 async fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    // Help new users set up their config file, unless they're about to run the wizard anyway
+    let mut config = Config::new()?;
+    let runs_wizard = matches!(
+        &args.command,
+        Some(Command::Config {
+            action: ConfigCommand::Init
+        })
+    );
+    if config.is_first_run() && !runs_wizard {
+        configs::setup::first_run(&mut config).await?;
+    }
+
+    // Subcommands don't need the board or the model list
+    match &args.command {
+        Some(Command::Config { action }) => return configs::command::run(action).await,
+        Some(Command::Cache { action }) => return handle_cache_command(action, &config),
+        Some(Command::Board { action }) => return handle_board_command(action).await,
+        Some(Command::History { action }) => return run_history_command(action),
+        Some(Command::Stats) => return print_stats(&PriceTable::new(&config)),
+        _ => {}
+    }
+
     // Create an API instance and get all available models from API
-    let api_instance = api::Instance::new()?;
+    let mut api_instance = api::Instance::new()?;
+    if !args.no_cache {
+        let mode = if args.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Enabled
+        };
+        api_instance.set_cache(build_cache(mode, &config)?);
+    }
     let models_response = api_instance.get_models().await?;
     let model_collection = ModelCollection::new(&models_response);
 
+    // Settings from the command line, then the config file
+    let mut options = GenerateOptions::default()
+        .prices(PriceTable::new(&config))
+        .language(
+            args.language
+                .or_else(|| config.get_language())
+                .unwrap_or_default(),
+        )
+        .used_clues(read_used_clues(args)?)
+        .targets(args.target.as_deref().map(split_list).unwrap_or_default())
+        .generation(GenerationTable::new(&config, args.generation.clone())?)
+        .rules(read_clue_rules(args, &config)?);
+    if let Some(max_cost) = args.max_cost {
+        options = options.max_cost(max_cost);
+    }
+
+    // Serve the REST API with the same instance, models and settings
+    if let Some(Command::Serve { bind }) = &args.command {
+        let state = ServerState::new(
+            api_instance,
+            model_collection,
+            default_model_ids()?,
+            options,
+        );
+        return serve(*bind, state).await;
+    }
+
     // If -g is set, display models and exit the program
     if args.get {
-        println!("{model_collection}");
+        println!(
+            "{}",
+            model_collection.filter(args.filter.as_deref(), args.all_models)
+        );
         return Ok(());
     }
 
@@ -27,68 +98,222 @@ async fn run(args: &Args) -> Result<(), Box<dyn Error>> {
     let selected_model_ids = select_models(args, &model_collection)?;
 
     // Various API calls and then build ClueCollection
-    let clue_collection =
-        obtain_clue_collection(args, api_instance, model_collection, &selected_model_ids).await?;
+    let (board, clue_collection) =
+        obtain_clue_collection(args, &api_instance, &selected_model_ids, &options).await?;
+
+    // Keep the game for `history review`, a broken history shouldn't cost the clues
+    if !args.no_history && !clue_collection.is_empty() {
+        match History::new()
+            .and_then(|history| history.record(&board, &clue_collection, args.game.as_deref()))
+        {
+            Ok(id) => eprintln!(
+                "Saved as game #{id}. Record how it went with `mastermind history review`"
+            ),
+            Err(e) => eprintln!("Failed to save the game to the history: {e}"),
+        }
+    }
+
+    // Explore the clues on the board instead of printing them
+    if args.tui {
+        let mut app = App::new(board, read_grid(args), clue_collection);
+        if !args.no_history {
+            if let Ok(history) = History::new() {
+                app = app.with_history(history, args.game.clone());
+            }
+        }
+        return run_tui(app, &api_instance, &selected_model_ids, &options).await;
+    }
 
     // Output
-    handle_output(args, &clue_collection)?;
+    handle_output(args, &clue_collection, &options.prices)?;
+
+    Ok(())
+}
+
+fn build_cache(mode: CacheMode, config: &Config) -> Result<Cache, Box<dyn Error>> {
+    let ttl = config
+        .get_cache_ttl()
+        .map_or(DEFAULT_TTL, Duration::from_secs);
+
+    Cache::new(mode, ttl)
+}
+
+fn handle_cache_command(action: &CacheCommand, config: &Config) -> Result<(), Box<dyn Error>> {
+    let cache = build_cache(CacheMode::Enabled, config)?;
+    match action {
+        CacheCommand::Clear => {
+            let count = cache.clear()?;
+            println!("Removed {count} cached response(s)");
+        }
+        CacheCommand::Stats => {
+            let stats = cache.stats()?;
+            println!(
+                "Location: {}\n\
+                Cached responses: {} ({} expired)\n\
+                Total size: {:.1} KiB",
+                cache.dir().display(),
+                stats.entries,
+                stats.expired,
+                stats.bytes as f64 / 1024.0
+            );
+        }
+    }
+    Ok(())
+}
 
+async fn handle_board_command(action: &BoardCommand) -> Result<(), Box<dyn Error>> {
+    match action {
+        BoardCommand::New {
+            words,
+            seed,
+            team,
+            output,
+            link,
+            avoid,
+        } => {
+            let words = match words {
+                Some(path) => generator::word_list(read_words_from_file(path)?),
+                None => generator::word_list(DEFAULT_WORDS.lines()),
+            };
+
+            // Print the seed so that the same board can be drawn again
+            let seed = seed.unwrap_or_else(rand::random);
+            let grid = generator::generate(&words, *team, seed)?;
+            eprintln!("Seed: {seed}");
+
+            if let Some(output_path) = output {
+                println!("Writing to file '{}'...", output_path.display());
+                write_content_to_file(output_path, grid.to_string())?;
+            } else if let (Some(link_path), Some(avoid_path)) = (link, avoid) {
+                let board = grid.to_board();
+                println!(
+                    "Writing to files '{}' and '{}'...",
+                    link_path.display(),
+                    avoid_path.display()
+                );
+                write_content_to_file(link_path, board.to_link().join("\n") + "\n")?;
+                write_content_to_file(avoid_path, board.to_avoid().join("\n") + "\n")?;
+            } else {
+                print!("{grid}");
+            }
+        }
+        BoardCommand::FromImage {
+            photo,
+            model,
+            team,
+            language,
+            output,
+        } => {
+            let model_id = match model {
+                Some(model_id) => model_id.clone(),
+                None => default_model_ids()?
+                    .into_iter()
+                    .next()
+                    .ok_or("No default model found. Pick a vision model with -m")?,
+            };
+
+            let language = match language {
+                Some(language) => *language,
+                None => Config::new()?.get_language().unwrap_or_default(),
+            };
+
+            println!("Reading the board with {model_id}...");
+            let api_instance = api::Instance::new()?;
+            let words = api_instance
+                .extract_board_words(photo, &model_id, language)
+                .await?;
+
+            let grid = review::review(&words, *team, language)?;
+            println!("Writing to file '{}'...", output.display());
+            write_content_to_file(output, grid.to_string())?;
+        }
+        BoardCommand::EndTurn { file } => {
+            let content = fs::read_to_string(file)
+                .map_err(|_| format!("Cannot find file: {}", file.display()))?;
+            let mut key = DuetKey::parse(&content)
+                .map_err(|e| format!("Invalid board file: {e} ({})", file.display()))?;
+
+            let tokens = key.end_turn()?;
+            fs::write(file, key.to_string())
+                .map_err(|_| format!("Failed to write to file: {}", file.display()))?;
+
+            if tokens == 0 {
+                println!("That was the last timer token. Sudden death: no more clues can be given");
+            } else {
+                println!("{tokens} timer token(s) left");
+            }
+        }
+    }
     Ok(())
 }
 
 /// If -m is present and has values, use the preferred language models.
 /// If -m is present but doesn't have a value, prompt selection menu.
-/// If -m is not present, use the default from config file and then environment variable.
+/// If -m is not present, use the defaults from environment variable and then config file.
+/// If -s is set, save the selection as the new defaults.
 fn select_models(
     args: &Args,
     model_collection: &ModelCollection,
 ) -> Result<Vec<String>, Box<dyn Error>> {
     let selected_model_ids = match &args.models {
-        Some(model_ids) if model_ids[0] == "interactive" => model_collection.prompt_selection(),
+        Some(model_ids) if model_ids[0] == "interactive" => model_collection.prompt_selection()?,
         Some(model_ids) => model_ids.to_owned(),
         None => {
-            dotenv().ok();
-            if let Ok(envvar) = env::var("DEFAULT_MODEL_ID") {
-                vec![envvar]
-            } else if let Some(config) = Config::new()?.get_default_model() {
-                vec![config.to_string()]
-            } else {
+            let default_model_ids = default_model_ids()?;
+            if default_model_ids.is_empty() {
                 return Err("No default model found".into());
             }
+            default_model_ids
         }
     };
 
+    // Validate each selected model, before it can be saved as a default
+    for model_id in &selected_model_ids {
+        model_collection.validate_model_id(model_id)?;
+    }
+
+    if args.save_default && !selected_model_ids.is_empty() {
+        let mut config = Config::new()?;
+        config.set_default_models(&selected_model_ids)?;
+        config.save(config.path())?;
+        println!("Saved {} as default", selected_model_ids.join(", "));
+    }
+
     Ok(selected_model_ids)
 }
 
+/// Default models from environment variable and then config file
+fn default_model_ids() -> Result<Vec<String>, Box<dyn Error>> {
+    dotenv().ok();
+    let default_model_ids = match env::var("DEFAULT_MODEL_ID") {
+        Ok(envvar) => split_list(&envvar),
+        Err(_) => Config::new()?.get_default_models(),
+    };
+
+    Ok(default_model_ids)
+}
+
 async fn obtain_clue_collection(
     args: &Args,
-    api_instance: api::Instance,
-    model_collection: ModelCollection,
-    selected_model_ids: &Vec<String>,
-) -> Result<ClueCollection, Box<dyn Error>> {
-    // Read words from the two files
-    let link_words = read_words_from_file(args.to_link.as_ref().unwrap())?;
-    let avoid_words = read_words_from_file(args.to_avoid.as_ref().unwrap())?;
-
-    // Aggregate responses from each language model
-    let mut responses = vec![];
-    for model_id in selected_model_ids {
-        // Validate each selected model
-        model_collection.validate_model_id(model_id)?;
-        // Get response from API
-        let response = api_instance
-            .post_chat_completions(&link_words, &avoid_words, model_id)
-            .await?;
-        responses.push(response);
-    }
+    api_instance: &api::Instance,
+    selected_model_ids: &[String],
+    options: &GenerateOptions,
+) -> Result<(Board, ClueCollection), Box<dyn Error>> {
+    // Read words from the board file, inline lists or the two files
+    let board = read_board(args, options.language)?;
 
-    // Build ClueCollection
-    let clue_collection = ClueCollection::new(responses);
-    Ok(clue_collection)
+    // Get responses from API and build ClueCollection
+    let clue_collection = api_instance
+        .generate_clues(&board, selected_model_ids, options)
+        .await?;
+    Ok((board, clue_collection))
 }
 
-fn handle_output(args: &Args, clue_collection: &ClueCollection) -> Result<(), Box<dyn Error>> {
+fn handle_output(
+    args: &Args,
+    clue_collection: &ClueCollection,
+    prices: &PriceTable,
+) -> Result<(), Box<dyn Error>> {
     if clue_collection.is_empty() {
         println!("The language model didn't return any useful clues. Maybe try again?");
     } else if let Some(output_path) = &args.output {
@@ -100,7 +325,7 @@ fn handle_output(args: &Args, clue_collection: &ClueCollection) -> Result<(), Bo
 
     // If -t is set, output token usage information
     if args.token {
-        clue_collection.display_token_info();
+        clue_collection.display_token_info(prices);
     }
     Ok(())
 }
@@ -1,42 +1,125 @@
-use dialoguer::MultiSelect;
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Table};
+use dialoguer::{Input, MultiSelect};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 
-use crate::json::models::ModelsResponse;
+use crate::json::models::{Model, ModelsResponse};
 
+/// Models with these words in their IDs can't produce clues (embedding, speech, image, etc.)
+const NON_CHAT_PATTERNS: [&str; 14] = [
+    "embed",
+    "whisper",
+    "tts",
+    "transcribe",
+    "dall-e",
+    "image",
+    "moderation",
+    "guard",
+    "rerank",
+    "realtime",
+    "audio",
+    "babbage",
+    "davinci",
+    "stable-diffusion",
+];
+
+#[derive(Serialize)]
+#[serde(transparent)]
 pub struct ModelCollection {
-    model_ids: Vec<String>,
+    models: Vec<Model>,
 }
 
 impl ModelCollection {
     pub fn new(response: &ModelsResponse) -> Self {
-        let mut model_ids: Vec<String> = vec![];
-        response
+        let mut models: Vec<Model> = response
             .data
             .iter()
-            .for_each(|model| model_ids.push(model.id.trim().to_string()));
+            .map(|model| Model {
+                id: model.id.trim().to_string(),
+                ..model.clone()
+            })
+            .collect();
 
-        model_ids.sort();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
 
-        Self { model_ids }
+        Self { models }
     }
 
-    pub fn prompt_selection(&self) -> Vec<String> {
-        let chosen_indexes = MultiSelect::new()
-            .with_prompt("[Space] to select, [Enter] to confirm\nYour choice(s)")
-            .items(&self.model_ids)
-            .interact()
-            .unwrap();
+    pub fn iter(&self) -> std::slice::Iter<'_, Model> {
+        self.models.iter()
+    }
 
-        let chosen_model_ids = chosen_indexes
+    /// Keep only the models whose IDs contain `pattern` (case-insensitive).
+    /// Models that can't produce clues are left out unless `include_all` is set.
+    pub fn filter(&self, pattern: Option<&str>, include_all: bool) -> Self {
+        let pattern = pattern.map(str::to_lowercase);
+        let models = self
+            .models
             .iter()
-            .map(|&i| self.model_ids[i].to_string())
+            .filter(|model| include_all || is_chat_model(&model.id))
+            .filter(|model| match &pattern {
+                Some(pattern) => model.id.to_lowercase().contains(pattern),
+                None => true,
+            })
+            .cloned()
             .collect();
 
-        chosen_model_ids
+        Self { models }
+    }
+
+    /// Search and pick models from a menu. Fails without a terminal, or when the user
+    /// cancels with Ctrl-C.
+    pub fn prompt_selection(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let chat_models = self.filter(None, false);
+        let matcher = SkimMatcherV2::default();
+
+        loop {
+            let query: String = Input::new()
+                .with_prompt("Search models (leave empty to list all)")
+                .allow_empty(true)
+                .interact_text()?;
+
+            // Rank the models by how well they match the query
+            let mut matches: Vec<(i64, &str)> = chat_models
+                .models
+                .iter()
+                .filter_map(|model| {
+                    if query.is_empty() {
+                        return Some((0, model.id.as_str()));
+                    }
+                    matcher
+                        .fuzzy_match(&model.id, &query)
+                        .map(|score| (score, model.id.as_str()))
+                })
+                .collect();
+            matches.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+
+            if matches.is_empty() {
+                println!("No model matches '{query}'");
+                continue;
+            }
+
+            let items: Vec<&str> = matches.iter().map(|&(_, model_id)| model_id).collect();
+            let chosen_indexes = MultiSelect::new()
+                .with_prompt("[Space] to select, [Enter] to confirm\nYour choice(s)")
+                .items(&items)
+                .interact()?;
+
+            let chosen_model_ids = chosen_indexes
+                .iter()
+                .map(|&i| items[i].to_string())
+                .collect();
+
+            return Ok(chosen_model_ids);
+        }
     }
 
     pub fn validate_model_id(&self, model_id: &String) -> Result<(), Box<dyn std::error::Error>> {
-        if !self.model_ids.contains(model_id) {
+        if !self.models.iter().any(|model| &model.id == model_id) {
             return Err(
                 format!("{model_id} is not a valid language model from your provider").into(),
             );
@@ -44,14 +127,74 @@ impl ModelCollection {
 
         Ok(())
     }
+
+    fn generate_table(&self) -> Table {
+        let mut table = Table::new();
+
+        // Set up header and styles
+        table
+            .set_header(["Model", "Owned By", "Created", "Context"].map(|header| {
+                Cell::new(header)
+                    .add_attribute(Attribute::Bold)
+                    .set_alignment(CellAlignment::Center)
+            }))
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS);
+
+        // Add rows, leaving unknown metadata blank
+        for model in &self.models {
+            table.add_row(vec![
+                model.id.clone(),
+                model.owned_by.clone().unwrap_or_default(),
+                model.created.map(format_date).unwrap_or_default(),
+                model
+                    .context_length
+                    .map(|length| length.to_string())
+                    .unwrap_or_default(),
+            ]);
+        }
+
+        // Right-align the context length
+        let last_column = table
+            .column_mut(3)
+            .expect("The table should have 4 columns");
+        last_column.set_cell_alignment(CellAlignment::Right);
+
+        table
+    }
 }
 
 impl Display for ModelCollection {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.model_ids.join("\n"))
+        write!(f, "{}", self.generate_table())
     }
 }
 
+fn is_chat_model(model_id: &str) -> bool {
+    let model_id = model_id.to_lowercase();
+    !NON_CHAT_PATTERNS
+        .iter()
+        .any(|pattern| model_id.contains(pattern))
+}
+
+/// Format a Unix timestamp as YYYY-MM-DD
+pub(crate) fn format_date(timestamp: u64) -> String {
+    // Convert days since epoch to a civil date
+    // See: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (timestamp / 86400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +230,44 @@ mod tests {
             fs::read_to_string("resources/tests/expected_outputs/models.txt").unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_filter() {
+        let response: ModelsResponse = serde_json::from_str(
+            &fs::read_to_string("resources/tests/mock_responses/models.json").unwrap(),
+        )
+        .unwrap();
+        let model_collection = ModelCollection::new(&response);
+        let ids = |collection: ModelCollection| -> Vec<String> {
+            collection
+                .models
+                .into_iter()
+                .map(|model| model.id)
+                .collect()
+        };
+
+        // Speech and safety models are hidden by default
+        let chat_models = ids(model_collection.filter(None, false));
+        assert_eq!(chat_models.len(), 10);
+        assert!(!chat_models.iter().any(|id| id.contains("whisper")));
+        assert_eq!(ids(model_collection.filter(None, true)).len(), 13);
+
+        // Case-insensitive pattern
+        assert_eq!(
+            ids(model_collection.filter(Some("GEMMA"), false)),
+            vec!["gemma-7b-it", "gemma2-9b-it"]
+        );
+
+        // Validation still sees every model
+        assert!(model_collection
+            .validate_model_id(&"whisper-large-v3".to_string())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1725402373), "2024-09-03");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
 }
@@ -0,0 +1,178 @@
+use crate::configs::config::Config;
+use crate::json::chat_completions::Usage;
+
+/// Built-in prices in USD per million tokens: (model ID, prompt, completion).
+/// These may be out of date. Override them in the `[pricing]` table of the config file.
+const DEFAULT_PRICES: [(&str, f64, f64); 20] = [
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4-turbo", 10.0, 30.0),
+    ("gpt-4", 30.0, 60.0),
+    ("gpt-3.5-turbo", 0.5, 1.5),
+    ("o1", 15.0, 60.0),
+    ("o1-mini", 3.0, 12.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("llama-3.1-70b-versatile", 0.59, 0.79),
+    ("llama-3.1-8b-instant", 0.05, 0.08),
+    ("llama3-70b-8192", 0.59, 0.79),
+    ("llama3-8b-8192", 0.05, 0.08),
+    ("llama3-groq-70b-8192-tool-use-preview", 0.89, 0.89),
+    ("llama3-groq-8b-8192-tool-use-preview", 0.19, 0.19),
+    ("mixtral-8x7b-32768", 0.24, 0.24),
+    ("gemma2-9b-it", 0.2, 0.2),
+    ("gemma-7b-it", 0.07, 0.07),
+    ("mistral-large", 2.0, 6.0),
+];
+
+/// Price of a model in USD per million tokens
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Price {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl Price {
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt
+            + usage.completion_tokens as f64 * self.completion)
+            / 1_000_000.0
+    }
+}
+
+#[derive(Clone)]
+pub struct PriceTable {
+    prices: Vec<(String, Price)>,
+}
+
+impl PriceTable {
+    /// Combine the built-in prices with the ones from the config file, which take priority
+    pub fn new(config: &Config) -> Self {
+        let mut table = Self::default();
+        for (model_id, price) in config.get_prices() {
+            table.prices.retain(|(id, _)| id != &model_id);
+            table.prices.push((model_id, price));
+        }
+        table
+    }
+
+    /// Find the price of a model. Dated variants like `gpt-4o-2024-08-06` use the price of
+    /// the model without the date, and provider prefixes like `openai/` are ignored.
+    pub fn get(&self, model_id: &str) -> Option<Price> {
+        let find = |model_id: &str| {
+            self.prices
+                .iter()
+                .find(|(id, _)| id == model_id)
+                .map(|(_, price)| *price)
+        };
+        if let Some(price) = find(model_id) {
+            return Some(price);
+        }
+
+        let model_id = model_id.rsplit('/').next().unwrap_or(model_id);
+        find(model_id).or_else(|| find(without_date(model_id)?))
+    }
+
+    pub fn cost(&self, model_id: &str, usage: &Usage) -> Option<f64> {
+        self.get(model_id).map(|price| price.cost(usage))
+    }
+}
+
+/// Strip a release date suffix like `-2024-08-06` or `-20241022` from a model ID
+fn without_date(model_id: &str) -> Option<&str> {
+    let (model_id, date) = model_id.rsplit_once('-')?;
+    // `2024-08-06` is split on its last dash too
+    let (model_id, date) = if date.len() == 2 {
+        let (model_id, month) = model_id.rsplit_once('-')?;
+        let (model_id, year) = model_id.rsplit_once('-')?;
+        (model_id, [year, month, date].concat())
+    } else {
+        (model_id, date.to_string())
+    };
+
+    (date.len() == 8 && date.bytes().all(|byte| byte.is_ascii_digit())).then_some(model_id)
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let prices = DEFAULT_PRICES
+            .iter()
+            .map(|&(model_id, prompt, completion)| {
+                (model_id.to_string(), Price { prompt, completion })
+            })
+            .collect();
+
+        Self { prices }
+    }
+}
+
+/// Format a cost in USD, with enough precision for small numbers
+pub fn format_cost(cost: f64) -> String {
+    if cost >= 0.01 || cost == 0.0 {
+        format!("${cost:.2}")
+    } else {
+        format!("${cost:.5}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn usage(prompt_tokens: usize, completion_tokens: usize) -> Usage {
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    #[test]
+    fn test_get() {
+        let table = PriceTable::default();
+        assert_eq!(table.get("gpt-4o").unwrap().prompt, 2.5);
+        assert_eq!(table.get("gpt-4o-mini-2024-07-18").unwrap().prompt, 0.15);
+        assert_eq!(table.get("openai/gpt-4o-2024-08-06").unwrap().prompt, 2.5);
+        assert_eq!(table.get("claude-3-5-sonnet-20241022").unwrap().prompt, 3.0);
+        assert!(table.get("some-local-model").is_none());
+
+        // Only a date can follow the model ID
+        assert!(table.get("gpt-4-32k").is_none());
+        assert!(table.get("gpt-4o-audio-preview").is_none());
+        assert!(table.get("gpt-4o-2024-8-6").is_none());
+    }
+
+    #[test]
+    fn test_cost() {
+        let table = PriceTable::default();
+        let cost = table.cost("gpt-4o", &usage(1_000_000, 100_000)).unwrap();
+        assert!((cost - 3.5).abs() < 1e-9);
+        assert_eq!(format_cost(0.000123), "$0.00012");
+        assert_eq!(format_cost(1.5), "$1.50");
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let temp_dir = tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_file,
+            "[pricing.\"gpt-4o\"]\nprompt = 1\ncompletion = 2.0\n\n\
+            [pricing.\"my-local-model\"]\nprompt = 0\ncompletion = 0\n",
+        )
+        .unwrap();
+
+        let table = PriceTable::new(&Config::load(&config_file).unwrap());
+        assert_eq!(
+            table.get("gpt-4o"),
+            Some(Price {
+                prompt: 1.0,
+                completion: 2.0
+            })
+        );
+        assert_eq!(table.cost("my-local-model", &usage(100, 100)), Some(0.0));
+    }
+}
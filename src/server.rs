@@ -0,0 +1,373 @@
+use axum::extract::{DefaultBodyLimit, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::api::{check_budget, check_targets, GenerateOptions, Instance};
+use crate::board::{Board, MAX_WORDS};
+use crate::clues::clue_collection::ClueCollection;
+use crate::language::Language;
+use crate::model_collection::ModelCollection;
+
+/// Largest request body accepted, in bytes
+const MAX_BODY_SIZE: usize = 16 * 1024;
+const MAX_MODELS: usize = 5;
+
+pub struct ServerState {
+    api_instance: Instance,
+    model_collection: ModelCollection,
+    default_model_ids: Vec<String>,
+    /// Settings from the command line and the config file, like the generation settings
+    /// and the clue rules. Requests can change the language, used clues and targets.
+    defaults: GenerateOptions,
+}
+
+impl ServerState {
+    pub fn new(
+        api_instance: Instance,
+        model_collection: ModelCollection,
+        default_model_ids: Vec<String>,
+        defaults: GenerateOptions,
+    ) -> Self {
+        Self {
+            api_instance,
+            model_collection,
+            default_model_ids,
+            defaults,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CluesRequest {
+    link: Vec<String>,
+    avoid: Vec<String>,
+    #[serde(default)]
+    models: Vec<String>,
+    #[serde(default)]
+    language: Option<Language>,
+    #[serde(default)]
+    used_clues: Vec<String>,
+    #[serde(default)]
+    targets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelsQuery {
+    filter: Option<String>,
+    #[serde(default)]
+    all: bool,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+pub fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/models", get(models))
+        .route("/clues", post(clues))
+        .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+        .with_state(Arc::new(state))
+}
+
+/// Serve the REST API until the process is stopped
+pub async fn serve(bind: SocketAddr, state: ServerState) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(bind)
+        .await
+        .map_err(|e| format!("Failed to listen on {bind}: {e}"))?;
+
+    println!("Listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(state)).await?;
+
+    Ok(())
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+async fn models(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<ModelsQuery>,
+) -> Json<ModelCollection> {
+    Json(
+        state
+            .model_collection
+            .filter(query.filter.as_deref(), query.all),
+    )
+}
+
+async fn clues(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<CluesRequest>,
+) -> Result<Json<ClueCollection>, ApiError> {
+    let bad_request = |message: String| ApiError(StatusCode::BAD_REQUEST, message);
+
+    // Validate the request before spending any tokens
+    if request.link.is_empty() {
+        return Err(bad_request("'link' must contain at least one word".into()));
+    }
+    if request.link.len() + request.avoid.len() > MAX_WORDS {
+        return Err(bad_request(format!(
+            "A board has at most {MAX_WORDS} words"
+        )));
+    }
+    if request.models.len() > MAX_MODELS {
+        return Err(bad_request(format!(
+            "At most {MAX_MODELS} models can be used at once"
+        )));
+    }
+
+    let model_ids = if request.models.is_empty() {
+        &state.default_model_ids
+    } else {
+        &request.models
+    };
+    if model_ids.is_empty() {
+        return Err(bad_request(
+            "No model given and no default model found".into(),
+        ));
+    }
+    for model_id in model_ids {
+        state
+            .model_collection
+            .validate_model_id(model_id)
+            .map_err(|e| bad_request(e.to_string()))?;
+    }
+
+    // Get responses from API and build ClueCollection
+    let options = GenerateOptions {
+        language: request.language.unwrap_or(state.defaults.language),
+        used_clues: request.used_clues,
+        targets: request.targets,
+        ..state.defaults.clone()
+    };
+    let mut board = Board::new(request.link, request.avoid);
+    board.normalize(options.language).map_err(bad_request)?;
+    check_targets(&options.targets, &board, options.language).map_err(bad_request)?;
+    check_budget(&board, model_ids, &options)
+        .map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+    // Only the API server can fail from here on
+    let clue_collection = state
+        .api_instance
+        .generate_clues(&board, model_ids, &options)
+        .await
+        .map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+    Ok(Json(clue_collection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::config::Config;
+    use crate::generation::{GenerationParams, GenerationTable};
+    use httpmock::prelude::*;
+
+    async fn start_server(provider: &MockServer, defaults: GenerateOptions) -> String {
+        let api_instance = Instance::builder(provider.url("/")).build();
+        let models_response = api_instance.get_models().await.unwrap();
+        let state = ServerState::new(
+            api_instance,
+            ModelCollection::new(&models_response),
+            vec!["llama-3.1-70b-versatile".to_string()],
+            defaults,
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await });
+
+        format!("http://{address}")
+    }
+
+    async fn mock_provider() -> MockServer {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/models");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/models.json");
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions.json");
+        });
+        server
+    }
+
+    #[tokio::test]
+    async fn test_health_and_models() {
+        let provider = mock_provider().await;
+        let base_url = start_server(&provider, GenerateOptions::default()).await;
+
+        let health: serde_json::Value = reqwest::get(format!("{base_url}/health"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(health["status"], "ok");
+
+        let models: serde_json::Value = reqwest::get(format!("{base_url}/models?filter=gemma"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(models.as_array().unwrap().len(), 2);
+        assert_eq!(models[0]["id"], "gemma-7b-it");
+    }
+
+    #[tokio::test]
+    async fn test_clues() {
+        let provider = mock_provider().await;
+        let base_url = start_server(&provider, GenerateOptions::default()).await;
+        let client = reqwest::Client::new();
+
+        // Uses the default model
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&json!({
+                "link": ["sound", "bee", "bond", "tokyo", "scuba diver", "hospital", "walrus"],
+                "avoid": ["park", "penny"]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["clues"].as_array().unwrap().len(), 4);
+        assert_eq!(body["clues"][0]["clue_word"], "music");
+        assert_eq!(body["usage"][0]["usage"]["total_tokens"], 275);
+
+        // Targets must be words to link
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&json!({ "link": ["sound"], "avoid": ["park"], "targets": ["park"] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        // Unknown model
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&json!({ "link": ["sound"], "avoid": [], "models": ["nope"] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        // Too large
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&json!({ "link": ["a".repeat(MAX_BODY_SIZE)], "avoid": [] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 413);
+    }
+
+    #[tokio::test]
+    async fn test_clues_defaults() {
+        let provider = MockServer::start_async().await;
+        provider.mock(|when, then| {
+            when.method(GET).path("/models");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/models.json");
+        });
+        let mock = provider.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .body_contains(r#""temperature":0.3"#)
+                .body_contains("Gib 3-4");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/chat_completions.json");
+        });
+
+        // Settings from the config file apply to every request
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_file = temp_dir.path().join("config.toml");
+        std::fs::write(
+            &config_file,
+            "[clues]\nlanguage = \"de\"\nmin-clues = 3\nmax-clues = 4\n\n\
+            [generation]\ntemperature = 0.3\n",
+        )
+        .unwrap();
+        let config = Config::load(&config_file).unwrap();
+        let defaults = GenerateOptions {
+            language: config.get_language().unwrap_or_default(),
+            generation: GenerationTable::new(&config, GenerationParams::default()).unwrap(),
+            rules: config.get_clue_rules(),
+            ..Default::default()
+        };
+        let base_url = start_server(&provider, defaults).await;
+
+        let response = reqwest::Client::new()
+            .post(format!("{base_url}/clues"))
+            .json(&json!({ "link": ["sound", "bee", "bond"], "avoid": ["park"] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_clues_errors() {
+        let provider = MockServer::start_async().await;
+        provider.mock(|when, then| {
+            when.method(GET).path("/models");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("resources/tests/mock_responses/models.json");
+        });
+        let mock = provider.mock(|when, then| {
+            when.method(POST).path("/chat/completions");
+            then.status(500).body("Internal Server Error");
+        });
+        let client = reqwest::Client::new();
+        let board = json!({ "link": ["sound", "bee"], "avoid": ["park"] });
+
+        // Over budget, before anything is sent
+        let base_url = start_server(&provider, GenerateOptions::default().max_cost(0.0)).await;
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&board)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 422);
+        assert!(response.text().await.unwrap().contains("budget"));
+        mock.assert_hits(0);
+
+        // The API server failed
+        let base_url = start_server(&provider, GenerateOptions::default()).await;
+        let response = client
+            .post(format!("{base_url}/clues"))
+            .json(&board)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 502);
+        mock.assert_hits(1);
+    }
+}
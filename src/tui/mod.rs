@@ -0,0 +1,384 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::DefaultTerminal;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use crate::api::{GenerateOptions, Instance};
+use crate::board::grid::{Card, Grid, Role, SIZE};
+use crate::board::{looks_alike, Board, Team};
+use crate::clues::clue::Clue;
+use crate::clues::clue_collection::ClueCollection;
+use crate::history::History;
+
+mod ui;
+
+/// Where the arrow keys go
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Focus {
+    Clues,
+    Board,
+}
+
+/// What the event loop should do after a key press
+#[derive(PartialEq, Debug)]
+enum Action {
+    None,
+    Requery,
+    Quit,
+}
+
+/// State of the terminal UI: the cards on the board, the clues and what is selected
+pub struct App {
+    team: Team,
+    cards: Vec<Card>,
+    board: Board,
+    clues: ClueCollection,
+    selected_clue: usize,
+    cursor: usize,
+    focus: Focus,
+    status: String,
+    /// Where the clues of every re-query are saved, with the game file of `--game`
+    history: Option<(History, Option<PathBuf>)>,
+}
+
+impl App {
+    /// Show every card of `grid` with its role. Without a grid, e.g. for word lists,
+    /// the words to link are shown as your agents and the other words as neutral cards.
+    pub fn new(board: Board, grid: Option<Grid>, clues: ClueCollection) -> Self {
+        // Cards are spelled like the board, to match the linked words
+        let language = clues.language();
+        let (team, cards) = match grid {
+            Some(grid) => (
+                grid.team,
+                grid.cards
+                    .into_iter()
+                    .map(|card| Card {
+                        word: language.normalize(&card.word),
+                        ..card
+                    })
+                    .collect(),
+            ),
+            None => (Team::default(), cards_from_board(&board, Team::default())),
+        };
+
+        Self {
+            team,
+            cards,
+            board,
+            clues,
+            selected_clue: 0,
+            cursor: 0,
+            focus: Focus::Clues,
+            status: String::new(),
+            history: None,
+        }
+    }
+
+    /// Save the clues of every re-query to `history` as a new game, like the first query
+    pub fn with_history(mut self, history: History, game: Option<PathBuf>) -> Self {
+        self.history = Some((history, game));
+        self
+    }
+
+    pub fn selected_clue(&self) -> Option<&Clue> {
+        self.clues.iter().nth(self.selected_clue)
+    }
+
+    /// The board for the next request, without the revealed cards
+    pub fn remaining_board(&self) -> Board {
+        let revealed: Vec<String> = self
+            .cards
+            .iter()
+            .filter(|card| card.revealed)
+            .map(|card| card.word.clone())
+            .collect();
+        self.board.without(&revealed)
+    }
+
+    /// Replace the clues after a new request
+    pub fn set_clues(&mut self, clues: ClueCollection) {
+        self.status = if clues.is_empty() {
+            "The language model didn't return any useful clues. Maybe try again?".to_string()
+        } else {
+            format!("{} new clue(s)", clues.len())
+        };
+        self.clues = clues;
+        self.selected_clue = 0;
+    }
+
+    /// Save the clues for `board` to the history, if any, and say so in the status line
+    fn record_clues(&mut self, board: &Board) {
+        let Some((history, game)) = &self.history else {
+            return;
+        };
+        if self.clues.is_empty() {
+            return;
+        }
+        match history.record(board, &self.clues, game.as_deref()) {
+            Ok(id) => self.status.push_str(&format!(", saved as game #{id}")),
+            Err(e) => self.status = format!("Failed to save the game to the history: {e}"),
+        }
+    }
+
+    /// Headers of the clue table, in the language of the clues
+    fn clues_headers(&self) -> [&'static str; 4] {
+        self.clues.language().table_headers()
+    }
+
+    /// Whether `card` is linked by the selected clue
+    fn is_linked(&self, card: &Card) -> bool {
+        let language = self.clues.language();
+        self.selected_clue().is_some_and(|clue| {
+            clue.linked_words()
+                .iter()
+                .any(|word| language.same_word(word, &card.word))
+        })
+    }
+
+    /// Whether `card` is a word to avoid that looks like the selected clue, which guessers
+    /// may pick by mistake
+    fn is_nearby(&self, card: &Card) -> bool {
+        card.role != Role::of(self.team)
+            && !card.revealed
+            && self.selected_clue().is_some_and(|clue| {
+                looks_alike(clue.clue_word(), &card.word, self.clues.language())
+            })
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        match (self.focus, code) {
+            (_, KeyCode::Char('q') | KeyCode::Esc) => return Action::Quit,
+            (_, KeyCode::Tab) => {
+                self.focus = match self.focus {
+                    Focus::Clues => Focus::Board,
+                    Focus::Board => Focus::Clues,
+                };
+            }
+            (_, KeyCode::Char('r')) => {
+                if self.remaining_board().to_link().is_empty() {
+                    self.status = "All your agents are revealed".to_string();
+                } else {
+                    return Action::Requery;
+                }
+            }
+
+            // Scroll through the clues
+            (Focus::Clues, KeyCode::Up | KeyCode::Char('k')) => {
+                self.selected_clue = self.selected_clue.saturating_sub(1);
+            }
+            (Focus::Clues, KeyCode::Down | KeyCode::Char('j'))
+                if self.selected_clue + 1 < self.clues.len() =>
+            {
+                self.selected_clue += 1;
+            }
+
+            // Move around the board and mark cards revealed
+            (Focus::Board, KeyCode::Left | KeyCode::Char('h')) => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            (Focus::Board, KeyCode::Right | KeyCode::Char('l')) => {
+                self.cursor = (self.cursor + 1).min(self.cards.len().saturating_sub(1));
+            }
+            (Focus::Board, KeyCode::Up | KeyCode::Char('k')) => {
+                self.cursor = self.cursor.saturating_sub(SIZE);
+            }
+            (Focus::Board, KeyCode::Down | KeyCode::Char('j'))
+                if self.cursor + SIZE < self.cards.len() =>
+            {
+                self.cursor += SIZE;
+            }
+            (Focus::Board, KeyCode::Char(' ') | KeyCode::Enter) => {
+                if let Some(card) = self.cards.get_mut(self.cursor) {
+                    card.revealed = !card.revealed;
+                    self.status = "Press r for clues without the revealed cards".to_string();
+                }
+            }
+            _ => {}
+        }
+
+        Action::None
+    }
+}
+
+/// Run the terminal UI until the user quits. Re-queries use the same models and options.
+pub async fn run(
+    mut app: App,
+    api_instance: &Instance,
+    model_ids: &[String],
+    options: &GenerateOptions,
+) -> Result<(), Box<dyn Error>> {
+    if !io::stdout().is_terminal() {
+        return Err("The terminal UI needs an interactive terminal".into());
+    }
+
+    // Always give the terminal back, even after an error
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, api_instance, model_ids, options).await;
+    ratatui::restore();
+
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    api_instance: &Instance,
+    model_ids: &[String],
+    options: &GenerateOptions,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key.code) {
+            Action::Quit => return Ok(()),
+            Action::Requery => {
+                app.status = "Asking for new clues...".to_string();
+                terminal.draw(|frame| ui::draw(frame, app))?;
+
+                // Errors are shown in the status line, the board is still worth keeping
+                let board = app.remaining_board();
+                match api_instance
+                    .generate_clues(&board, model_ids, options)
+                    .await
+                {
+                    Ok(clues) => {
+                        app.set_clues(clues);
+                        app.record_clues(&board);
+                    }
+                    Err(e) => app.status = e.to_string(),
+                }
+            }
+            Action::None => {}
+        }
+    }
+}
+
+/// Cards for a board read from word lists, where the roles of the words to avoid are unknown
+fn cards_from_board(board: &Board, team: Team) -> Vec<Card> {
+    let card = |word: &String, role: Role| Card {
+        word: word.clone(),
+        role,
+        revealed: false,
+    };
+
+    let to_link = board
+        .to_link()
+        .iter()
+        .map(|word| card(word, Role::of(team)));
+    let to_avoid = board.to_avoid().iter().map(|word| {
+        if board.assassins().contains(word) {
+            card(word, Role::Assassin)
+        } else {
+            card(word, Role::Neutral)
+        }
+    });
+    to_link.chain(to_avoid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::chat_completions::ChatCompletionsResponse;
+    use crate::language::Language;
+    use std::fs;
+
+    fn clues(content: &str) -> ClueCollection {
+        let response: ChatCompletionsResponse = serde_json::from_value(serde_json::json!({
+            "model": "model",
+            "choices": [{ "message": { "role": "assistant", "content": content } }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+        .unwrap();
+        ClueCollection::new(vec![response])
+    }
+
+    fn grid_app() -> App {
+        let grid = Grid::parse(&fs::read_to_string("examples/grid.board").unwrap()).unwrap();
+        let board = grid.to_board();
+        App::new(board, Some(grid), clues("water, 2, whale, fish"))
+    }
+
+    #[test]
+    fn test_new() {
+        let board = Board::new(
+            vec!["sound".to_string()],
+            vec!["park".to_string(), "penny".to_string()],
+        )
+        .with_assassins(vec!["penny".to_string()]);
+        let app = App::new(board, None, clues(""));
+
+        let roles: Vec<Role> = app.cards.iter().map(|card| card.role).collect();
+        assert_eq!(roles, [Role::Red, Role::Neutral, Role::Assassin]);
+        assert!(app.selected_clue().is_none());
+    }
+
+    #[test]
+    fn test_reveal_and_requery() {
+        let mut app = grid_app();
+        let remaining = app.remaining_board().to_avoid().len();
+
+        // Reveal the first unrevealed card
+        let index = app.cards.iter().position(|card| !card.revealed).unwrap();
+        app.handle_key(KeyCode::Tab);
+        for _ in 0..index {
+            app.handle_key(KeyCode::Right);
+        }
+        app.handle_key(KeyCode::Char(' '));
+        assert!(app.cards[index].revealed);
+
+        let board = app.remaining_board();
+        assert_eq!(
+            board.to_link().len() + board.to_avoid().len(),
+            app.board.to_link().len() + remaining - 1
+        );
+        assert!(!board.to_link().contains(&app.cards[index].word));
+        assert!(!board.to_avoid().contains(&app.cards[index].word));
+
+        assert_eq!(app.handle_key(KeyCode::Char('r')), Action::Requery);
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::Quit);
+
+        // The new clues are saved as a game of their own
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history = History::with_path(temp_dir.path().join("history.jsonl"));
+        let mut app = app.with_history(history, None);
+        app.set_clues(clues("ocean, 2, whale, fish"));
+        app.record_clues(&board);
+        assert!(app.status.ends_with("saved as game #1"));
+        let (history, _) = app.history.as_ref().unwrap();
+        assert_eq!(history.get(1).unwrap().to_link, board.to_link());
+    }
+
+    #[test]
+    fn test_is_linked() {
+        let board = Board::new(vec!["Straße".to_string()], vec!["park".to_string()]);
+        let clues = clues("weg, 2, STRASSE, park").with_language(Language::German);
+        let app = App::new(board, None, clues);
+        assert!(app.is_linked(&app.cards[0]));
+        assert!(app.is_linked(&app.cards[1]));
+    }
+
+    #[test]
+    fn test_scroll_clues() {
+        let mut app = App::new(
+            Board::new(vec!["a".to_string()], vec![]),
+            None,
+            clues("water, 2, whale, fish\nmusic, 3, sound, bee, drum"),
+        );
+
+        // Sorted by count
+        assert_eq!(app.selected_clue().unwrap().clue_word(), "music");
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected_clue().unwrap().clue_word(), "water");
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.selected_clue().unwrap().clue_word(), "music");
+    }
+}
@@ -0,0 +1,217 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::{App, Focus};
+use crate::board::grid::{Card, Role, SIZE};
+
+const HELP: &str = "↑↓ clues · Tab board · Space reveal · r new clues · q quit";
+
+pub(super) fn draw(frame: &mut Frame, app: &App) {
+    let [main_area, status_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+    let [board_area, side_area] =
+        Layout::horizontal([Constraint::Fill(3), Constraint::Fill(2)]).areas(main_area);
+    let [clues_area, details_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(6)]).areas(side_area);
+
+    draw_board(frame, app, board_area);
+    draw_clues(frame, app, clues_area);
+    draw_details(frame, app, details_area);
+
+    let status = Line::from(vec![
+        Span::styled(&app.status, Style::new().add_modifier(Modifier::BOLD)),
+        Span::raw(if app.status.is_empty() { "" } else { "  " }),
+        Span::styled(HELP, Style::new().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(status), status_area);
+}
+
+fn draw_board(frame: &mut Frame, app: &App, area: Rect) {
+    let block = focus_block("Board", app.focus == Focus::Board);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = app.cards.len().div_ceil(SIZE);
+    let row_areas = Layout::vertical(vec![Constraint::Max(5); rows]).split(inner);
+    for (row, row_area) in row_areas.iter().enumerate() {
+        let card_areas = Layout::horizontal([Constraint::Fill(1); SIZE]).split(*row_area);
+        for (column, card_area) in card_areas.iter().enumerate() {
+            let index = row * SIZE + column;
+            if let Some(card) = app.cards.get(index) {
+                let selected = app.focus == Focus::Board && index == app.cursor;
+                draw_card(frame, app, card, selected, *card_area);
+            }
+        }
+    }
+}
+
+fn draw_card(frame: &mut Frame, app: &App, card: &Card, selected: bool, area: Rect) {
+    let mut block = Block::bordered();
+
+    // The selected clue shows which cards it links and which ones it may be confused with
+    if app.is_linked(card) {
+        block = block
+            .border_type(BorderType::Thick)
+            .border_style(Style::new().fg(Color::Green))
+            .title("linked");
+    } else if app.is_nearby(card) {
+        block = block
+            .border_type(BorderType::Thick)
+            .border_style(Style::new().fg(Color::Yellow))
+            .title("risky");
+    }
+    if selected {
+        block = block
+            .border_type(BorderType::Double)
+            .border_style(Style::new().fg(Color::White).add_modifier(Modifier::BOLD));
+    }
+
+    let style = if card.revealed {
+        Style::new()
+            .fg(role_color(card.role))
+            .add_modifier(Modifier::DIM | Modifier::CROSSED_OUT)
+    } else {
+        Style::new().fg(Color::Black).bg(role_color(card.role))
+    };
+
+    // Center the word vertically
+    let padding = area.height.saturating_sub(3) / 2;
+    let mut lines = vec![Line::raw(""); padding as usize];
+    lines.push(Line::styled(card.word.as_str(), style));
+
+    frame.render_widget(
+        Paragraph::new(lines).centered().style(style).block(block),
+        area,
+    );
+}
+
+fn draw_clues(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .clues
+        .iter()
+        .map(|clue| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} {}", clue.clue_word(), clue.count()),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("  {}", clue.linked_words().join(", "))),
+            ]))
+        })
+        .collect();
+
+    let [clue, count, _, _] = app.clues_headers();
+    let list = List::new(items)
+        .block(focus_block(
+            &format!("{clue} ({count})"),
+            app.focus == Focus::Clues,
+        ))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    let mut state =
+        ListState::default().with_selected(app.selected_clue().map(|_| app.selected_clue));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let lines = match app.selected_clue() {
+        Some(clue) => {
+            let risky: Vec<&str> = app
+                .cards
+                .iter()
+                .filter(|card| app.is_nearby(card))
+                .map(|card| card.word.as_str())
+                .collect();
+            let [_, _, linked_words, source] = app.clues_headers();
+
+            vec![
+                Line::from(vec![
+                    Span::styled(format!("{linked_words}: "), Style::new().fg(Color::Green)),
+                    Span::raw(clue.linked_words().join(", ")),
+                ]),
+                Line::from(vec![
+                    Span::styled("Risky: ", Style::new().fg(Color::Yellow)),
+                    Span::raw(if risky.is_empty() {
+                        "-".to_string()
+                    } else {
+                        risky.join(", ")
+                    }),
+                ]),
+                Line::from(vec![
+                    Span::styled(format!("{source}: "), Style::new().fg(Color::DarkGray)),
+                    Span::raw(clue.source()),
+                ]),
+            ]
+        }
+        None => vec![Line::raw("No clues. Press r to ask again")],
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered()),
+        area,
+    );
+}
+
+fn focus_block(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title.to_string());
+    if focused {
+        block.border_style(Style::new().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
+
+fn role_color(role: Role) -> Color {
+    match role {
+        Role::Red => Color::Red,
+        Role::Blue => Color::Blue,
+        Role::Neutral => Color::Gray,
+        Role::Assassin => Color::DarkGray,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::clues::clue_collection::ClueCollection;
+    use crate::json::chat_completions::ChatCompletionsResponse;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_draw() {
+        let response: ChatCompletionsResponse = serde_json::from_value(serde_json::json!({
+            "model": "model",
+            "choices": [{ "message": { "content": "music, 2, sound, bee" } }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+        }))
+        .unwrap();
+        let board = Board::new(
+            vec!["sound".to_string(), "bee".to_string()],
+            vec!["musical".to_string(), "park".to_string()],
+        );
+        let app = App::new(board, None, ClueCollection::new(vec![response]));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("music 2"));
+        assert!(screen.contains("linked"));
+        assert!(screen.contains("Risky: musical"));
+        assert!(screen.contains("park"));
+    }
+}